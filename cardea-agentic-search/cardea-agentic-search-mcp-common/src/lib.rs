@@ -31,7 +31,11 @@ pub struct QdrantSearchHit {
     #[schemars(description = "The score of the point")]
     pub score: f64,
     #[schemars(description = "The payload of the point")]
+    #[serde(default)]
     pub payload: HashMap<String, Value>,
-    #[schemars(description = "The vector of the point")]
-    pub vector: Vec<f64>,
+    #[schemars(
+        description = "The vectors of the point, either a single vector or a map of named vectors. `None` if not requested"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Value>,
 }
@@ -1,17 +1,22 @@
 mod search;
 
 use anyhow::{anyhow, bail};
+use cardea_tidb_mcp_common::{SslMode, TidbConnectionUrl};
 use clap::{Parser, Subcommand, ValueEnum};
 use mysql::*;
-use regex::Regex;
 use rmcp::transport::{
     sse_server::SseServer,
     streamable_http_server::{StreamableHttpService, session::local::LocalSessionManager},
 };
 use rustls::crypto::{CryptoProvider, ring::default_provider};
 use search::AgenticSearchServer;
-use std::{env, path::PathBuf};
-use tracing::{error, info};
+use std::{
+    env,
+    path::PathBuf,
+    sync::{Arc, atomic::AtomicBool},
+    time::Duration,
+};
+use tracing::{error, info, warn};
 use tracing_subscriber::{self, layer::SubscriberExt, util::SubscriberInitExt};
 
 const DEFAULT_SOCKET_ADDR: &str = "127.0.0.1:8009";
@@ -26,6 +31,10 @@ struct Args {
     /// Transport type to use
     #[arg(short, long, value_enum, default_value = "stream-http")]
     transport: TransportType,
+    /// Interval in seconds between the health checks of the TiDB connection, in the `tidb` and
+    /// `search` modes. 0 disables them
+    #[arg(long, default_value = "30")]
+    tidb_health_check_interval: u64,
     /// Search mode to enable
     #[command(subcommand)]
     search_mode: SearchMode,
@@ -38,9 +47,19 @@ enum SearchMode {
         /// Name of the collection to search in Qdrant
         #[arg(long, required = true)]
         qdrant_collection: String,
-        /// The name of the field in the payload that contains the source of the document
-        #[arg(long, required = true)]
-        qdrant_payload_field: String,
+        /// The names of the fields in the payload that contain the source of the document.
+        /// Multiple fields (e.g. `title,text,url`) are combined into one result
+        #[arg(long, required = true, value_delimiter = ',')]
+        qdrant_payload_field: Vec<String>,
+        /// The name of the field in the payload to use when none of the payload fields is present
+        #[arg(long)]
+        qdrant_fallback_payload_field: Option<String>,
+        /// How to handle results whose payload contains none of the payload fields
+        #[arg(long, value_enum, default_value = "skip")]
+        qdrant_missing_payload: MissingPayload,
+        /// The name of the vector to search, for collections with named vectors
+        #[arg(long)]
+        qdrant_vector_name: Option<String>,
         /// Maximum number of results to return
         #[arg(long, default_value = "10")]
         limit: u64,
@@ -55,9 +74,9 @@ enum SearchMode {
     Tidb {
         /// Path to the SSL CA certificate. On macOS, this is typically
         /// `/etc/ssl/cert.pem`. On Debian/Ubuntu/Arch Linux, it's typically
-        /// `/etc/ssl/certs/ca-certificates.crt`.
-        #[arg(long, required = true)]
-        tidb_ssl_ca: PathBuf,
+        /// `/etc/ssl/certs/ca-certificates.crt`. If not set, the built-in root certificates are used
+        #[arg(long)]
+        tidb_ssl_ca: Option<PathBuf>,
         /// Table name to search in TiDB
         #[arg(long, required = true)]
         tidb_table_name: String,
@@ -76,14 +95,24 @@ enum SearchMode {
         /// Name of the collection to search in Qdrant
         #[arg(long, required = true)]
         qdrant_collection: String,
-        /// The name of the field in the payload that contains the source of the document
-        #[arg(long, required = true)]
-        qdrant_payload_field: String,
+        /// The names of the fields in the payload that contain the source of the document.
+        /// Multiple fields (e.g. `title,text,url`) are combined into one result
+        #[arg(long, required = true, value_delimiter = ',')]
+        qdrant_payload_field: Vec<String>,
+        /// The name of the field in the payload to use when none of the payload fields is present
+        #[arg(long)]
+        qdrant_fallback_payload_field: Option<String>,
+        /// How to handle results whose payload contains none of the payload fields
+        #[arg(long, value_enum, default_value = "skip")]
+        qdrant_missing_payload: MissingPayload,
+        /// The name of the vector to search, for collections with named vectors
+        #[arg(long)]
+        qdrant_vector_name: Option<String>,
         /// Path to the SSL CA certificate. On macOS, this is typically
         /// `/etc/ssl/cert.pem`. On Debian/Ubuntu/Arch Linux, it's typically
-        /// `/etc/ssl/certs/ca-certificates.crt`.
-        #[arg(long, required = true)]
-        tidb_ssl_ca: PathBuf,
+        /// `/etc/ssl/certs/ca-certificates.crt`. If not set, the built-in root certificates are used
+        #[arg(long)]
+        tidb_ssl_ca: Option<PathBuf>,
         /// Table name to search in TiDB
         #[arg(long, required = true)]
         tidb_table_name: String,
@@ -102,6 +131,17 @@ enum SearchMode {
     },
 }
 
+/// How to handle vector search results whose payload contains none of the payload fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MissingPayload {
+    /// Leave the result out
+    Skip,
+    /// Fail the search
+    Error,
+    /// Use the whole payload rendered as JSON
+    Json,
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 enum TransportType {
     Sse,
@@ -124,6 +164,9 @@ async fn main() -> anyhow::Result<()> {
         SearchMode::Qdrant {
             qdrant_collection,
             qdrant_payload_field,
+            qdrant_fallback_payload_field,
+            qdrant_missing_payload,
+            qdrant_vector_name,
             limit,
             score_threshold,
             embedding_service,
@@ -145,7 +188,10 @@ async fn main() -> anyhow::Result<()> {
                     api_key: qdrant_api_key,
                     base_url: qdrant_base_url,
                     collection: qdrant_collection,
-                    payload_source: qdrant_payload_field,
+                    payload_fields: qdrant_payload_field,
+                    fallback_payload_field: qdrant_fallback_payload_field,
+                    missing_payload: qdrant_missing_payload,
+                    vector_name: qdrant_vector_name,
                 }),
                 tidb_config: None,
                 limit,
@@ -166,63 +212,12 @@ async fn main() -> anyhow::Result<()> {
         } => {
             info!("Enabling keyword search mode");
 
-            // parse connection string
-            let (username, password, host, port, database) = match env::var("TIDB_CONNECTION") {
-                Ok(ref conn) => {
-                    parse_tidb_conn_str(conn.as_str()).ok_or_else(|| anyhow!(
-                        "Invalid connection string! The pattern should be `mysql://<USERNAME>:<PASSWORD>@<HOST>:<PORT>/<DATABASE>`"
-                    ))?
-                }
-                Err(e) => {
-                    let error_message = format!("Failed to get TIDB_CONNECTION: {e}");
-                    error!(error_message);
-                    bail!(error_message);
-                }
-            };
-
-            // convert port to u16
-            let port = port.parse::<u16>().map_err(|e| {
-                let error_message = format!("Failed to parse TIDB_PORT: {e}");
-                error!(error_message);
-                anyhow!(error_message)
-            })?;
-
             // parse chat service api key
             let chat_service_api_key = env::var("CHAT_SERVICE_API_KEY").ok();
 
-            CryptoProvider::install_default(default_provider()).map_err(|e| {
-                let err_msg = format!("Failed to install default crypto provider: {e:?}");
-                error!("{}", err_msg);
-                anyhow!(err_msg)
-            })?;
-
-            // create connection options
-            info!("Creating connection options for TiDB Cloud...");
-            let opts = OptsBuilder::new()
-                .ip_or_hostname(Some(host))
-                .tcp_port(port)
-                .user(Some(username))
-                .pass(Some(password))
-                .db_name(Some(database.clone()))
-                .ssl_opts(Some(
-                    SslOpts::default().with_root_cert_path(Some(tidb_ssl_ca)),
-                ));
-
-            // create connection pool
-            info!("Creating connection pool...");
-            let pool = Pool::new(opts).map_err(|e| {
-                let error_message = format!("Failed to create connection pool: {e}");
-                error!(error_message);
-                anyhow!(error_message)
-            })?;
-
             AgenticSearchConfig {
                 qdrant_config: None,
-                tidb_config: Some(TiDBConfig {
-                    database,
-                    table_name: tidb_table_name,
-                    pool,
-                }),
+                tidb_config: Some(create_tidb_config(tidb_ssl_ca, tidb_table_name)?),
                 limit,
                 score_threshold,
                 chat_service: Some(ServiceConfig {
@@ -235,6 +230,9 @@ async fn main() -> anyhow::Result<()> {
         SearchMode::Search {
             qdrant_collection,
             qdrant_payload_field,
+            qdrant_fallback_payload_field,
+            qdrant_missing_payload,
+            qdrant_vector_name,
             tidb_ssl_ca,
             tidb_table_name,
             limit,
@@ -251,71 +249,23 @@ async fn main() -> anyhow::Result<()> {
             // parse qdrant api key
             let qdrant_api_key = env::var("QDRANT_API_KEY").ok();
 
-            // parse connection string
-            let (tidb_username, tidb_password, tidb_host, tidb_port, tidb_database) = match env::var("TIDB_CONNECTION") {
-                Ok(ref conn) => {
-                    parse_tidb_conn_str(conn.as_str()).ok_or_else(|| anyhow!(
-                        "Invalid connection string! The pattern should be `mysql://<USERNAME>:<PASSWORD>@<HOST>:<PORT>/<DATABASE>`"
-                    ))?
-                }
-                Err(e) => {
-                    let error_message = format!("Failed to get TIDB_CONNECTION: {e}");
-                    error!(error_message);
-                    bail!(error_message);
-                }
-            };
-
-            // convert port to u16
-            let tidb_port = tidb_port.parse::<u16>().map_err(|e| {
-                let error_message = format!("Failed to parse TIDB_PORT: {e}");
-                error!(error_message);
-                anyhow!(error_message)
-            })?;
-
             // parse chat service api key
             let chat_service_api_key = env::var("CHAT_SERVICE_API_KEY").ok();
 
             // parse embedding service api key
             let embedding_service_api_key = env::var("EMBEDDING_SERVICE_API_KEY").ok();
 
-            CryptoProvider::install_default(default_provider()).map_err(|e| {
-                let err_msg = format!("Failed to install default crypto provider: {e:?}");
-                error!("{}", err_msg);
-                anyhow!(err_msg)
-            })?;
-
-            // create connection options
-            info!("Creating connection options for TiDB Cloud...");
-            let opts = OptsBuilder::new()
-                .ip_or_hostname(Some(tidb_host))
-                .tcp_port(tidb_port)
-                .user(Some(tidb_username))
-                .pass(Some(tidb_password))
-                .db_name(Some(tidb_database.clone()))
-                .ssl_opts(Some(
-                    SslOpts::default().with_root_cert_path(Some(tidb_ssl_ca)),
-                ));
-
-            // create connection pool
-            info!("Creating connection pool...");
-            let pool = Pool::new(opts).map_err(|e| {
-                let error_message = format!("Failed to create connection pool: {e}");
-                error!(error_message);
-                anyhow!(error_message)
-            })?;
-
             AgenticSearchConfig {
                 qdrant_config: Some(QdrantConfig {
                     api_key: qdrant_api_key,
                     base_url: qdrant_base_url,
                     collection: qdrant_collection,
-                    payload_source: qdrant_payload_field,
-                }),
-                tidb_config: Some(TiDBConfig {
-                    database: tidb_database,
-                    table_name: tidb_table_name,
-                    pool,
+                    payload_fields: qdrant_payload_field,
+                    fallback_payload_field: qdrant_fallback_payload_field,
+                    missing_payload: qdrant_missing_payload,
+                    vector_name: qdrant_vector_name,
                 }),
+                tidb_config: Some(create_tidb_config(tidb_ssl_ca, tidb_table_name)?),
                 limit,
                 score_threshold,
                 chat_service: Some(ServiceConfig {
@@ -330,6 +280,18 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    if let Some(tidb_config) = &search_config.tidb_config
+        && args.tidb_health_check_interval > 0
+    {
+        tokio::spawn(cardea_tidb_mcp_common::health_check(
+            tidb_config.pool.clone(),
+            tidb_config.database.clone(),
+            tidb_config.table_name.clone(),
+            tidb_config.schema_checked.clone(),
+            Duration::from_secs(args.tidb_health_check_interval),
+        ));
+    }
+
     info!(
         "Starting Cardea Agentic Search MCP server on {}",
         args.socket_addr
@@ -377,7 +339,13 @@ pub struct QdrantConfig {
     pub api_key: Option<String>,
     pub base_url: String,
     pub collection: String,
-    pub payload_source: String,
+    /// payload fields combined into the source of a search result
+    pub payload_fields: Vec<String>,
+    /// payload field used when none of the payload fields is present
+    pub fallback_payload_field: Option<String>,
+    pub missing_payload: MissingPayload,
+    /// name of the vector to search, for collections with named vectors
+    pub vector_name: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -385,6 +353,8 @@ pub struct TiDBConfig {
     pub database: String,
     pub table_name: String,
     pub pool: Pool,
+    /// whether the version and the table were checked since the database was last unreachable
+    pub schema_checked: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone)]
@@ -393,16 +363,64 @@ pub struct ServiceConfig {
     pub api_key: Option<String>,
 }
 
-fn parse_tidb_conn_str(conn_str: &str) -> Option<(String, String, String, String, String)> {
-    let re = Regex::new(r"^mysql://([^:]+):([^@]+)@([^:/]+):(\d+)/(.+)$").unwrap();
-    if let Some(caps) = re.captures(conn_str) {
-        let username = caps.get(1)?.as_str().to_string();
-        let password = caps.get(2)?.as_str().to_string();
-        let host = caps.get(3)?.as_str().to_string();
-        let port = caps.get(4)?.as_str().to_string();
-        let database = caps.get(5)?.as_str().to_string();
-        Some((username, password, host, port, database))
-    } else {
-        None
+/// Connect to TiDB with the `TIDB_CONNECTION` environment variable, and check that the table
+/// exists, so that a missing table fails the startup.
+fn create_tidb_config(ssl_ca: Option<PathBuf>, table_name: String) -> anyhow::Result<TiDBConfig> {
+    // parse connection string
+    let url = match env::var("TIDB_CONNECTION") {
+        Ok(conn) => conn.parse::<TidbConnectionUrl>().map_err(|e| {
+            let error_message = format!(
+                "Invalid TIDB_CONNECTION: {e}. The pattern should be `mysql://<USERNAME>:<PASSWORD>@<HOST>[:<PORT>]/<DATABASE>[?ssl-mode=<MODE>]`"
+            );
+            error!(error_message);
+            anyhow!(error_message)
+        })?,
+        Err(e) => {
+            let error_message = format!("Failed to get TIDB_CONNECTION: {e}");
+            error!(error_message);
+            bail!(error_message);
+        }
+    };
+    let database = url.database.clone();
+
+    CryptoProvider::install_default(default_provider()).map_err(|e| {
+        let err_msg = format!("Failed to install default crypto provider: {e:?}");
+        error!("{}", err_msg);
+        anyhow!(err_msg)
+    })?;
+
+    info!("TLS mode: {:?}", url.ssl_mode);
+    if url.ssl_mode == SslMode::Disabled && ssl_ca.is_some() {
+        warn!("TLS is disabled, ignoring --tidb-ssl-ca");
     }
+
+    // create connection options
+    info!("Creating connection options for TiDB...");
+    let opts = OptsBuilder::new()
+        .ip_or_hostname(Some(url.host))
+        .tcp_port(url.port)
+        .user(Some(url.username))
+        .pass(url.password)
+        .db_name(Some(url.database))
+        .ssl_opts(url.ssl_mode.ssl_opts(ssl_ca));
+
+    // create connection pool
+    info!("Creating connection pool...");
+    let pool = Pool::new(opts).map_err(|e| {
+        let error_message = format!("Failed to create connection pool: {e}");
+        error!(error_message);
+        anyhow!(error_message)
+    })?;
+
+    // check the schema once, later searches only check it again after a reconnection
+    let schema_checked = Arc::new(AtomicBool::new(false));
+    cardea_tidb_mcp_common::get_conn(&pool, &database, &table_name, &schema_checked)
+        .map_err(|e| anyhow!(e.message.to_string()))?;
+
+    Ok(TiDBConfig {
+        database,
+        table_name,
+        pool,
+        schema_checked,
+    })
 }
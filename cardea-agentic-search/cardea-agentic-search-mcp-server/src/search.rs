@@ -1,4 +1,4 @@
-use crate::AgenticSearchConfig;
+use crate::{AgenticSearchConfig, MissingPayload, QdrantConfig, TiDBConfig};
 use cardea_agentic_search_mcp_common::{QdrantSearchHit, SearchRequest, TidbSearchHit};
 use endpoints::{
     chat::{
@@ -7,7 +7,7 @@ use endpoints::{
     },
     embeddings::{EmbeddingRequest, EmbeddingsResponse, InputText},
 };
-use mysql::prelude::*;
+use mysql::{PooledConn, prelude::*};
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
 use rmcp::{
     ErrorData as McpError, RoleServer, ServerHandler,
@@ -16,8 +16,9 @@ use rmcp::{
     service::RequestContext,
     tool, tool_handler, tool_router,
 };
+use serde::Deserialize;
 use serde_json::{Value, json};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, error, info, warn};
 
 const DEFAULT_PROMPT_KEYWORD_EXTRACTOR: &str = r#"
@@ -107,15 +108,37 @@ impl AgenticSearchServer {
 
         if !hits.is_empty() {
             let qdrant_config = self.config.qdrant_config.as_ref().unwrap();
-            let payload_source = &qdrant_config.payload_source;
             info!(
                 "Extracting the payload ({}) of the vector search results...",
-                payload_source
+                qdrant_config.payload_fields.join(", ")
             );
             let mut output = Vec::new();
             for hit in hits {
-                let source = hit.payload.get(payload_source).unwrap().as_str().unwrap();
-                output.push(source.to_string());
+                match extract_source(qdrant_config, &hit.payload) {
+                    Some(source) => output.push(source),
+                    None => match qdrant_config.missing_payload {
+                        MissingPayload::Skip => {
+                            warn!(
+                                "Skipping a vector search result (score: {}) whose payload contains none of the payload fields",
+                                hit.score
+                            );
+                        }
+                        MissingPayload::Json => output.push(json!(hit.payload).to_string()),
+                        MissingPayload::Error => {
+                            let error_message = format!(
+                                "The payload of a vector search result (score: {}) contains none of the payload fields: {}",
+                                hit.score,
+                                qdrant_config.payload_fields.join(", ")
+                            );
+                            error!("{}", error_message);
+                            return Err(McpError::new(
+                                ErrorCode::INTERNAL_ERROR,
+                                error_message,
+                                None,
+                            ));
+                        }
+                    },
+                }
             }
 
             info!("Vector search done! 🎉");
@@ -268,18 +291,21 @@ impl AgenticSearchServer {
             Some(qdrant_config) => {
                 let base_url = qdrant_config.base_url.trim_end_matches('/');
                 let url = format!(
-                    "{}/collections/{}/points/search",
+                    "{}/collections/{}/points/query",
                     base_url, qdrant_config.collection
                 );
 
                 // build params
-                let params = json!({
-                    "vector": vector.as_ref().to_vec(),
+                let mut params = json!({
+                    "query": vector.as_ref().to_vec(),
                     "limit": self.config.limit,
-                    "with_payload": true,
-                    "with_vector": true,
+                    "with_payload": with_payload(qdrant_config),
+                    "with_vector": false,
                     "score_threshold": self.config.score_threshold,
                 });
+                if let Some(vector_name) = &qdrant_config.vector_name {
+                    params["using"] = json!(vector_name);
+                }
 
                 let response = match &qdrant_config.api_key {
                     Some(api_key) => reqwest::Client::new()
@@ -308,87 +334,41 @@ impl AgenticSearchServer {
                 };
 
                 let status = response.status();
+                let body = response.text().await.map_err(|e| {
+                    let err_msg = format!("Failed to search points: {e}");
+                    error!("{}", err_msg);
+                    McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None)
+                })?;
+
                 if !status.is_success() {
-                    let error_message =
-                        format!("Failed to send search request to Qdrant server. Status: {status}");
+                    // Qdrant reports errors as `{"status": {"error": "..."}, "time": ...}`
+                    let detail = match serde_json::from_str::<QdrantErrorResponse>(&body) {
+                        Ok(QdrantErrorResponse {
+                            status: QdrantErrorStatus { error },
+                        }) => error,
+                        Err(_) => body,
+                    };
+                    let error_message = format!("Failed to search points: {detail}");
                     error!("{}", error_message);
                     return Err(McpError::new(
                         ErrorCode::INTERNAL_ERROR,
                         error_message,
-                        None,
+                        Some(json!({ "status": status.as_u16(), "error": detail })),
                     ));
                 }
 
-                match response.json::<Value>().await {
-                    Ok(json) => match json.get("result") {
-                        Some(result) => {
-                            let hits = result
-                                .as_array()
-                                .unwrap()
-                                .iter()
-                                .map(|v| QdrantSearchHit {
-                                    score: v.get("score").unwrap().as_f64().unwrap(),
-                                    payload: v
-                                        .get("payload")
-                                        .unwrap()
-                                        .as_object()
-                                        .unwrap()
-                                        .to_owned()
-                                        .into_iter()
-                                        .map(|(k, v)| (k.to_string(), v.clone()))
-                                        .collect(),
-                                    vector: v
-                                        .get("vector")
-                                        .unwrap()
-                                        .as_array()
-                                        .unwrap()
-                                        .to_owned()
-                                        .iter()
-                                        .map(|v| v.as_f64().unwrap())
-                                        .collect::<Vec<f64>>(),
-                                })
-                                .collect();
-
-                            Ok(hits)
-                        }
-                        None => {
-                            debug!(
-                                "Qdrant search response:\n{}",
-                                serde_json::to_string_pretty(&json).unwrap()
-                            );
-
-                            match json.get("status") {
-                                Some(status) => {
-                                    let error_message = format!(
-                                        "Failed to search points. {}",
-                                        status.get("error").unwrap().as_str().unwrap()
-                                    );
-                                    error!("{}", error_message);
-                                    Err(McpError::new(
-                                        ErrorCode::INTERNAL_ERROR,
-                                        error_message,
-                                        None,
-                                    ))
-                                }
-                                None => {
-                                    let error_message = "Failed to search points. ";
-                                    error!("{}", error_message);
-                                    Err(McpError::new(
-                                        ErrorCode::INTERNAL_ERROR,
-                                        error_message,
-                                        None,
-                                    ))
-                                }
-                            }
-                        }
-                    },
+                match serde_json::from_str::<QdrantQueryResponse>(&body) {
+                    Ok(response) => Ok(response.result.points),
                     Err(e) => {
-                        let error_message = format!("Failed to search points: {e}");
+                        debug!("Qdrant search response:\n{}", body);
+
+                        let error_message =
+                            format!("Failed to search points: malformed response from Qdrant: {e}");
                         error!("{}", error_message);
                         Err(McpError::new(
                             ErrorCode::INTERNAL_ERROR,
                             error_message,
-                            None,
+                            Some(json!({ "response": body })),
                         ))
                     }
                 }
@@ -492,72 +472,7 @@ impl AgenticSearchServer {
     ) -> Result<Vec<TidbSearchHit>, McpError> {
         match &self.config.tidb_config {
             Some(tidb_config) => {
-                // get connection
-                debug!("Getting connection to TiDB Cloud...");
-                let mut conn = tidb_config.pool.get_conn().map_err(|e| {
-                    let error_message = format!("Failed to get connection: {e}");
-
-                    error!(error_message);
-
-                    McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-                })?;
-
-                // test connection
-                debug!("Testing connection...");
-                let version: String = match conn.query_first("SELECT VERSION()").map_err(|e| {
-                    let error_message = format!("Failed to query version: {e}");
-
-                    error!(error_message);
-
-                    McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-                })? {
-                    Some(version) => version,
-                    None => {
-                        let error_message = "Failed to query version";
-
-                        error!(error_message);
-
-                        return Err(McpError::new(
-                            ErrorCode::INTERNAL_ERROR,
-                            error_message,
-                            None,
-                        ));
-                    }
-                };
-                debug!("Connected to TiDB Cloud! Version: {}", version);
-
-                // check if table exists
-                debug!("Checking if table exists...");
-                let check_table_sql = format!(
-                    "SELECT COUNT(*) FROM information_schema.tables
-                WHERE table_schema = '{}' AND table_name = '{}'",
-                    tidb_config.database, tidb_config.table_name
-                );
-                let table_exists: i32 = conn
-                    .query_first(&check_table_sql)
-                    .map_err(|e| {
-                        let error_message = format!("Failed to check table: {e}");
-
-                        error!(error_message);
-
-                        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-                    })?
-                    .unwrap_or(0);
-
-                if table_exists == 0 {
-                    let error_message = format!(
-                        "Not found table `{}` in database `{}`",
-                        tidb_config.table_name, tidb_config.database
-                    );
-
-                    error!(error_message);
-
-                    return Err(McpError::new(
-                        ErrorCode::INTERNAL_ERROR,
-                        error_message,
-                        None,
-                    ));
-                }
+                let mut conn = get_tidb_conn(tidb_config)?;
 
                 // execute full-text search
 
@@ -676,3 +591,261 @@ impl ServerHandler for AgenticSearchServer {
         }
     }
 }
+
+/// Build the `with_payload` parameter: only the payload fields used to build the search results,
+/// or the whole payload if it may be rendered as JSON.
+fn with_payload(qdrant_config: &QdrantConfig) -> Value {
+    match qdrant_config.missing_payload {
+        MissingPayload::Json => json!(true),
+        _ => {
+            let mut fields = qdrant_config.payload_fields.clone();
+            fields.extend(qdrant_config.fallback_payload_field.clone());
+            json!(fields)
+        }
+    }
+}
+
+/// Combine the payload fields of a vector search result into its source, falling back to the
+/// fallback payload field. Returns `None` if none of the fields is present.
+fn extract_source(
+    qdrant_config: &QdrantConfig,
+    payload: &HashMap<String, Value>,
+) -> Option<String> {
+    let fields: Vec<(&str, &Value)> = qdrant_config
+        .payload_fields
+        .iter()
+        .filter_map(|field| {
+            payload
+                .get(field)
+                .filter(|value| !value.is_null())
+                .map(|value| (field.as_str(), value))
+        })
+        .collect();
+
+    match fields.as_slice() {
+        [] => qdrant_config
+            .fallback_payload_field
+            .as_ref()
+            .and_then(|field| payload.get(field))
+            .filter(|value| !value.is_null())
+            .map(render_value),
+        // a single payload field is returned as is
+        [(_, value)] if qdrant_config.payload_fields.len() == 1 => Some(render_value(value)),
+        fields => Some(
+            fields
+                .iter()
+                .map(|(field, value)| format!("{field}: {}", render_value(value)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+    }
+}
+
+/// Render a payload value as text: strings as is, other values as JSON.
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Response of the Qdrant query API
+#[derive(Debug, Deserialize)]
+struct QdrantQueryResponse {
+    result: QdrantQueryResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantQueryResult {
+    points: Vec<QdrantSearchHit>,
+}
+
+/// Error envelope of the Qdrant REST API
+#[derive(Debug, Deserialize)]
+struct QdrantErrorResponse {
+    status: QdrantErrorStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantErrorStatus {
+    error: String,
+}
+
+/// Get a connection from the pool. The version and the table are only checked on the first
+/// connection after the database was unreachable, as found by the health check.
+fn get_tidb_conn(tidb_config: &TiDBConfig) -> Result<PooledConn, McpError> {
+    cardea_tidb_mcp_common::get_conn(
+        &tidb_config.pool,
+        &tidb_config.database,
+        &tidb_config.table_name,
+        &tidb_config.schema_checked,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, http::StatusCode};
+
+    /// Start a mock Qdrant server answering every request with the given status and body.
+    async fn mock_qdrant(status: StatusCode, body: impl ToString) -> AgenticSearchServer {
+        let body = body.to_string();
+        let app = Router::new().fallback(move || {
+            let body = body.clone();
+            async move { (status, body) }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        AgenticSearchServer::new(AgenticSearchConfig {
+            qdrant_config: Some(QdrantConfig {
+                api_key: None,
+                base_url: format!("http://{addr}"),
+                collection: "test".to_string(),
+                payload_fields: vec!["full_text".to_string()],
+                fallback_payload_field: None,
+                missing_payload: MissingPayload::Skip,
+                vector_name: None,
+            }),
+            tidb_config: None,
+            limit: 10,
+            score_threshold: 0.0,
+            chat_service: None,
+            embedding_service: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn search_in_qdrant_without_vectors_and_time() {
+        let server = mock_qdrant(
+            StatusCode::OK,
+            json!({
+                "result": {
+                    "points": [
+                        { "id": 1, "score": 0.9, "payload": { "full_text": "hello" } },
+                        { "id": 2, "score": 0.8 }
+                    ]
+                }
+            }),
+        )
+        .await;
+
+        let hits = server.search_in_qdrant([0.1, 0.2]).await.unwrap();
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].payload["full_text"], "hello");
+        assert!(hits[0].vector.is_none());
+        assert!(hits[1].payload.is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_in_qdrant_with_named_vectors() {
+        let server = mock_qdrant(
+            StatusCode::OK,
+            json!({
+                "result": {
+                    "points": [{
+                        "id": 1,
+                        "score": 0.9,
+                        "payload": {},
+                        "vector": { "dense": [0.1, 0.2] }
+                    }]
+                },
+                "time": 0.01
+            }),
+        )
+        .await;
+
+        let hits = server.search_in_qdrant([0.1, 0.2]).await.unwrap();
+
+        assert_eq!(hits[0].vector, Some(json!({ "dense": [0.1, 0.2] })));
+    }
+
+    #[tokio::test]
+    async fn search_in_qdrant_returns_error_detail() {
+        let server = mock_qdrant(
+            StatusCode::NOT_FOUND,
+            json!({
+                "status": { "error": "Not found: Collection `test` doesn't exist!" },
+                "time": 0.0
+            }),
+        )
+        .await;
+
+        let error = server.search_in_qdrant([0.1, 0.2]).await.unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Failed to search points: Not found: Collection `test` doesn't exist!"
+        );
+        assert_eq!(error.data.unwrap()["status"], 404);
+    }
+
+    #[tokio::test]
+    async fn search_in_qdrant_rejects_malformed_response() {
+        let server = mock_qdrant(
+            StatusCode::OK,
+            json!({ "result": { "points": [{ "id": 1 }] } }),
+        )
+        .await;
+
+        let error = server.search_in_qdrant([0.1, 0.2]).await.unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert!(error.message.contains("missing field `score`"));
+    }
+
+    fn qdrant_config(
+        payload_fields: &[&str],
+        fallback_payload_field: Option<&str>,
+    ) -> QdrantConfig {
+        QdrantConfig {
+            api_key: None,
+            base_url: String::new(),
+            collection: "test".to_string(),
+            payload_fields: payload_fields
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+            fallback_payload_field: fallback_payload_field.map(str::to_string),
+            vector_name: None,
+            missing_payload: MissingPayload::Skip,
+        }
+    }
+
+    fn payload(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn extract_source_combines_payload_fields() {
+        let qdrant_config = qdrant_config(&["title", "text", "url"], None);
+
+        let source = extract_source(
+            &qdrant_config,
+            &payload(json!({ "title": "Hello", "text": "World", "page": 3 })),
+        );
+
+        assert_eq!(source.as_deref(), Some("title: Hello\ntext: World"));
+    }
+
+    #[test]
+    fn extract_source_falls_back_to_another_field() {
+        let qdrant_config = qdrant_config(&["text"], Some("summary"));
+
+        assert_eq!(
+            extract_source(&qdrant_config, &payload(json!({ "text": ["a", "b"] }))).as_deref(),
+            Some(r#"["a","b"]"#)
+        );
+        assert_eq!(
+            extract_source(&qdrant_config, &payload(json!({ "summary": "short" }))).as_deref(),
+            Some("short")
+        );
+        assert_eq!(
+            extract_source(&qdrant_config, &payload(json!({ "text": null }))),
+            None
+        );
+    }
+}
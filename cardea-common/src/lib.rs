@@ -0,0 +1,34 @@
+use std::path::Path;
+
+/// Build an http client trusting the CA certificates of `ca_cert` in addition to the built-in
+/// roots, and presenting the client certificate of `client_cert` and `client_key` for mutual TLS.
+/// All the files are PEM-encoded.
+pub fn build_http_client(
+    ca_cert: Option<&Path>,
+    client_cert: Option<&Path>,
+    client_key: Option<&Path>,
+) -> anyhow::Result<reqwest::Client> {
+    let mut client_builder = reqwest::Client::builder();
+    if let Some(ca_cert) = ca_cert {
+        let pem = std::fs::read(ca_cert).map_err(|e| {
+            anyhow::anyhow!("Failed to read CA certificate {}: {e}", ca_cert.display())
+        })?;
+        for cert in reqwest::Certificate::from_pem_bundle(&pem)? {
+            client_builder = client_builder.add_root_certificate(cert);
+        }
+    }
+    if let (Some(client_cert), Some(client_key)) = (client_cert, client_key) {
+        let mut pem = std::fs::read(client_cert).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read client certificate {}: {e}",
+                client_cert.display()
+            )
+        })?;
+        pem.extend(std::fs::read(client_key).map_err(|e| {
+            anyhow::anyhow!("Failed to read client key {}: {e}", client_key.display())
+        })?);
+        client_builder = client_builder.identity(reqwest::Identity::from_pem(&pem)?);
+    }
+
+    Ok(client_builder.build()?)
+}
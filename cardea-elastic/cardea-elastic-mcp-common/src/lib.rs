@@ -86,6 +86,12 @@ pub struct AliasInfo {
 pub struct SearchRequest {
     /// user query
     pub query: String,
+    /// whether to page through the results. If `true` and more results are available, the response contains a cursor to fetch the next page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paginate: Option<bool>,
+    /// cursor returned by the previous call, used to fetch the next page of results. The query must be the same as in the previous call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -97,6 +103,9 @@ pub struct SearchResponse {
     #[serde(rename = "_shards")]
     pub shards: Shards,
     pub hits: Hits,
+    /// opaque cursor to fetch the next page of results. `None` if there are no more results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 impl From<CallToolResult> for SearchResponse {
     fn from(result: CallToolResult) -> Self {
@@ -151,3 +160,463 @@ pub struct Reason {
     /// A human-readable explanation of the error, in English.
     pub reason: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AggregateRequest {
+    /// optional user query used to narrow down the documents to aggregate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// optional Elasticsearch query DSL clause applied as a filter, e.g. `{"term": {"status": "open"}}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<serde_json::Value>,
+    /// aggregations to compute
+    pub aggregations: Vec<Aggregation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Aggregation {
+    /// name of the aggregation, used to identify its result
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: AggregationKind,
+    /// sub-aggregations computed for each bucket. Only valid for bucket aggregations (terms, date_histogram, range).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aggregations: Vec<Aggregation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AggregationKind {
+    /// group documents by the unique values of a field
+    Terms {
+        /// field to group by, usually a keyword field
+        field: String,
+        /// maximum number of buckets to return
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<u64>,
+    },
+    /// group documents by date intervals
+    DateHistogram {
+        /// date field to group by
+        field: String,
+        /// calendar-aware interval, e.g. `day`, `week`, `month`, `quarter`, `year`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        calendar_interval: Option<String>,
+        /// fixed interval, e.g. `12h`, `30m`, `7d`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fixed_interval: Option<String>,
+        /// date format of the bucket keys, e.g. `yyyy-MM-dd`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        /// time zone used for bucketing, e.g. `+01:00` or `Europe/Paris`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_zone: Option<String>,
+    },
+    /// group documents by numeric or date ranges
+    Range {
+        /// field to group by
+        field: String,
+        /// ranges to group by
+        ranges: Vec<AggregationRange>,
+    },
+    /// average of a numeric field
+    Avg {
+        /// numeric field
+        field: String,
+    },
+    /// sum of a numeric field
+    Sum {
+        /// numeric field
+        field: String,
+    },
+    /// minimum of a numeric field
+    Min {
+        /// numeric field
+        field: String,
+    },
+    /// maximum of a numeric field
+    Max {
+        /// numeric field
+        field: String,
+    },
+    /// approximate count of distinct values of a field
+    Cardinality {
+        /// field to count distinct values of
+        field: String,
+    },
+}
+impl AggregationKind {
+    /// Whether the aggregation produces buckets.
+    pub fn is_bucket(&self) -> bool {
+        matches!(
+            self,
+            AggregationKind::Terms { .. }
+                | AggregationKind::DateHistogram { .. }
+                | AggregationKind::Range { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AggregationRange {
+    /// optional key of the bucket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// lower bound (inclusive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<serde_json::Value>,
+    /// upper bound (exclusive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AggregateResponse {
+    /// The number of milliseconds it took Elasticsearch to run the request.
+    pub took: u64,
+    /// If `true`, the request timed out before completion; returned results may be partial or empty.
+    pub timed_out: bool,
+    /// number of documents matching the query
+    pub total: u64,
+    /// aggregation results
+    pub aggregations: Vec<AggregationResult>,
+}
+impl From<CallToolResult> for AggregateResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<AggregateResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AggregationResult {
+    /// name of the aggregation
+    pub name: String,
+    #[serde(flatten)]
+    pub value: AggregationValue,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AggregationValue {
+    /// result of a bucket aggregation
+    Buckets { buckets: Vec<Bucket> },
+    /// result of a metric aggregation. `None` if no document has a value for the field.
+    Metric { value: Option<f64> },
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Bucket {
+    /// bucket key
+    pub key: serde_json::Value,
+    /// formatted bucket key, e.g. the date of a date_histogram bucket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_as_string: Option<String>,
+    /// number of documents in the bucket
+    pub doc_count: u64,
+    /// results of the sub-aggregations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aggregations: Vec<AggregationResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct KnnSearchRequest {
+    /// user query, embedded by the embedding service. Cannot be combined with `vector`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// query vector, must have the same dimension as the dense vector field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f64>>,
+    /// number of nearest neighbors to return, greater than 0. Defaults to the size configured on
+    /// the server, and is capped at the maximum configured on the server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub k: Option<u64>,
+    /// number of candidates to consider per shard, between `k` and 10000. Higher values improve
+    /// accuracy at the cost of speed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_candidates: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct HybridSearchRequest {
+    /// user query
+    pub query: String,
+    /// how to combine the keyword and knn results. Defaults to reciprocal rank fusion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fusion: Option<Fusion>,
+    /// number of candidates to consider per shard in the knn search, between the size configured
+    /// on the server and 10000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_candidates: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Fusion {
+    /// reciprocal rank fusion of the keyword and knn rankings
+    Rrf {
+        /// how much documents in lower ranks influence the final ranking
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rank_constant: Option<u64>,
+        /// number of documents of each result set taken into account
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rank_window_size: Option<u64>,
+    },
+    /// weighted sum of the keyword and knn scores
+    Linear {
+        /// weight of the keyword (BM25) score
+        keyword_weight: f64,
+        /// weight of the knn similarity score
+        vector_weight: f64,
+    },
+}
+impl Default for Fusion {
+    fn default() -> Self {
+        Fusion::Rrf {
+            rank_constant: None,
+            rank_window_size: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterHealthResponse {
+    /// name of the cluster
+    pub cluster_name: String,
+    /// health status of the cluster: `green`, `yellow` or `red`
+    pub status: String,
+    /// if `true`, the request timed out before the status was computed
+    pub timed_out: bool,
+    /// number of nodes in the cluster
+    pub number_of_nodes: u64,
+    /// number of nodes that are dedicated data nodes
+    pub number_of_data_nodes: u64,
+    /// number of active primary shards
+    pub active_primary_shards: u64,
+    /// total number of active primary and replica shards
+    pub active_shards: u64,
+    /// number of shards that are under relocation
+    pub relocating_shards: u64,
+    /// number of shards that are under initialization
+    pub initializing_shards: u64,
+    /// number of shards that are not allocated
+    pub unassigned_shards: u64,
+    /// number of shards whose allocation has been delayed by the timeout settings
+    pub delayed_unassigned_shards: u64,
+    /// number of cluster-level changes that have not yet been executed
+    pub number_of_pending_tasks: u64,
+    /// number of unfinished fetches
+    pub number_of_in_flight_fetch: u64,
+    /// time in milliseconds since the earliest initiated task is waiting for being performed
+    pub task_max_waiting_in_queue_millis: u64,
+    /// ratio of active shards in the cluster expressed as a percentage
+    pub active_shards_percent_as_number: f64,
+}
+impl From<CallToolResult> for ClusterHealthResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<ClusterHealthResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterStatsResponse {
+    /// name of the cluster
+    pub cluster_name: String,
+    /// unique identifier of the cluster
+    pub cluster_uuid: String,
+    /// health status of the cluster: `green`, `yellow` or `red`
+    pub status: String,
+    /// unix timestamp, in milliseconds, of when the statistics were last refreshed
+    pub timestamp: u64,
+    /// statistics about the indices with shards assigned to the selected nodes
+    pub indices: ClusterIndicesStats,
+    /// statistics about the nodes selected by the request
+    pub nodes: ClusterNodesStats,
+}
+impl From<CallToolResult> for ClusterStatsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<ClusterStatsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterIndicesStats {
+    /// total number of indices
+    pub count: u64,
+    pub shards: ClusterShardsStats,
+    pub docs: DocsStats,
+    pub store: StoreStats,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterShardsStats {
+    /// total number of shards
+    #[serde(default)]
+    pub total: u64,
+    /// number of primary shards
+    #[serde(default)]
+    pub primaries: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DocsStats {
+    /// total number of non-deleted documents
+    pub count: u64,
+    /// total number of deleted documents
+    pub deleted: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct StoreStats {
+    /// total size, in bytes, of all shards
+    pub size_in_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterNodesStats {
+    /// number of nodes by role, e.g. `total`, `data`, `master`, `ingest`
+    pub count: std::collections::BTreeMap<String, u64>,
+    /// versions of Elasticsearch used by the nodes
+    pub versions: Vec<String>,
+    pub jvm: ClusterJvmStats,
+    pub fs: ClusterFsStats,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterJvmStats {
+    /// uptime of the longest running JVM, in milliseconds
+    pub max_uptime_in_millis: u64,
+    pub mem: ClusterJvmMemStats,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterJvmMemStats {
+    /// heap memory currently in use, in bytes
+    pub heap_used_in_bytes: u64,
+    /// maximum heap memory available, in bytes
+    pub heap_max_in_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ClusterFsStats {
+    /// total size of all file stores, in bytes
+    pub total_in_bytes: u64,
+    /// unallocated disk space in all file stores, in bytes
+    pub free_in_bytes: u64,
+    /// disk space available to the JVM in all file stores, in bytes
+    pub available_in_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct NodesInfoResponse {
+    /// name of the cluster
+    pub cluster_name: String,
+    pub nodes: Vec<NodeInfo>,
+}
+impl From<CallToolResult> for NodesInfoResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<NodesInfoResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct NodeInfo {
+    /// node id
+    #[serde(default)]
+    pub id: String,
+    /// node name
+    pub name: String,
+    /// host and port where transport HTTP connections are accepted
+    pub transport_address: String,
+    /// host name
+    pub host: String,
+    /// ip address
+    pub ip: String,
+    /// Elasticsearch version
+    pub version: String,
+    /// roles assigned to the node, e.g. `master`, `data`, `ingest`
+    pub roles: Vec<String>,
+    /// custom node attributes
+    #[serde(default)]
+    pub attributes: std::collections::BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<NodeOsInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jvm: Option<NodeJvmInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct NodeOsInfo {
+    /// name of the operating system
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pretty_name: Option<String>,
+    /// architecture of the operating system
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// number of processors available to the JVM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_processors: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct NodeJvmInfo {
+    /// JVM version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem: Option<NodeJvmMemInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct NodeJvmMemInfo {
+    /// maximum heap memory, in bytes
+    pub heap_max_in_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct PendingTasksResponse {
+    pub tasks: Vec<PendingTask>,
+}
+impl From<CallToolResult> for PendingTasksResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<PendingTasksResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct PendingTask {
+    /// number that indicates when the task was inserted into the queue
+    pub insert_order: u64,
+    /// priority of the task, e.g. `URGENT`, `HIGH`, `NORMAL`
+    pub priority: String,
+    /// general description of the cluster task
+    pub source: String,
+    /// whether the task is currently being executed
+    pub executing: bool,
+    /// time, in milliseconds, the task has been waiting in the queue
+    pub time_in_queue_millis: u64,
+}
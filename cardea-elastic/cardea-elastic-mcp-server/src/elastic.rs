@@ -1,13 +1,31 @@
-use crate::CONNECTION_CONFIG;
+use crate::{CONNECTION_CONFIG, ConnectionConfig};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use cardea_elastic_mcp_common::*;
+use endpoints::embeddings::{EmbeddingRequest, EmbeddingsResponse, InputText};
+use reqwest::{Method, header::AUTHORIZATION};
 use rmcp::{
     ErrorData as McpError, ServerHandler,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::*,
     tool, tool_handler, tool_router,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use tracing::error;
+use tracing::{error, warn};
+
+/// Upper bound of the `num_candidates`, and so of the `k`, of a knn search in Elasticsearch
+pub const MAX_NUM_CANDIDATES: u64 = 10_000;
+
+/// Fields of the buckets in an Elasticsearch response, which sub-aggregations cannot be named after
+const RESERVED_BUCKET_FIELDS: [&str; 7] = [
+    "key",
+    "key_as_string",
+    "doc_count",
+    "from",
+    "from_as_string",
+    "to",
+    "to_as_string",
+];
 
 #[derive(Debug, Clone)]
 pub struct ElasticSearchServer {
@@ -45,20 +63,7 @@ impl ElasticSearchServer {
         let base_url = conn_config.base_url.trim_end_matches('/');
         let url = format!("{base_url}/_cat/indices?v=true&s=index&format=json");
 
-        // get api key
-        let api_key = conn_config.api_key;
-
-        let client = reqwest::Client::new();
-        let result = match api_key {
-            Some(api_key) => {
-                client
-                    .get(&url)
-                    .header("Authorization", api_key)
-                    .send()
-                    .await
-            }
-            None => client.get(&url).send().await,
-        };
+        let result = conn_config.request(Method::GET, &url).send().await;
 
         let response = match result {
             Ok(response) => response,
@@ -141,20 +146,7 @@ impl ElasticSearchServer {
         let base_url = conn_config.base_url.trim_end_matches('/');
         let url = format!("{base_url}/_cat/aliases?format=json&v=true");
 
-        // get api key
-        let api_key = conn_config.api_key;
-
-        let client = reqwest::Client::new();
-        let result = match api_key {
-            Some(api_key) => {
-                client
-                    .get(&url)
-                    .header("Authorization", api_key)
-                    .send()
-                    .await
-            }
-            None => client.get(&url).send().await,
-        };
+        let result = conn_config.request(Method::GET, &url).send().await;
 
         let response = match result {
             Ok(response) => response,
@@ -212,10 +204,16 @@ impl ElasticSearchServer {
         }
     }
 
-    #[tool(description = "Perform a keyword search")]
+    #[tool(
+        description = "Perform a keyword search. Set `paginate` to page through the results: if more results are available, the response then contains a cursor that can be passed to a follow-up call to fetch the next page."
+    )]
     async fn search(
         &self,
-        Parameters(SearchRequest { query }): Parameters<SearchRequest>,
+        Parameters(SearchRequest {
+            query,
+            paginate,
+            cursor,
+        }): Parameters<SearchRequest>,
     ) -> Result<CallToolResult, McpError> {
         // get connection config
         let conn_config = match CONNECTION_CONFIG.get() {
@@ -234,56 +232,372 @@ impl ElasticSearchServer {
             }
         };
 
-        let index = conn_config.index;
-        let fields = conn_config.fields;
+        let index = &conn_config.index;
+        let fields = &conn_config.fields;
         let size = conn_config.size;
 
         tracing::info!("index: {}", index);
         tracing::info!("fields: {:?}", fields);
         tracing::info!("size: {}", size);
 
-        // build url
-        let base_url = conn_config.base_url.trim_end_matches('/');
-        let url = format!("{base_url}/{index}/_search");
+        // without pagination, a plain search avoids the cost of a point in time
+        if !paginate.unwrap_or(false) && cursor.is_none() {
+            let body = json!({
+                "query": {
+                    "multi_match": {
+                        "query": query,
+                        "fields": fields
+                    },
+                },
+                "size": size
+            });
 
-        // get api key
-        let api_key = conn_config.api_key;
+            return send_search(&conn_config, &body).await;
+        }
 
-        let body = json!({
+        // open a point in time for the first page, or resume from the cursor
+        let (pit_id, search_after) = match cursor {
+            Some(cursor) => {
+                let cursor = Cursor::decode(&cursor)?;
+                if cursor.query != query {
+                    let error_message =
+                        "The query must be the same as in the call that returned the cursor";
+                    error!("{}", error_message);
+                    return Err(McpError::new(
+                        ErrorCode::INVALID_PARAMS,
+                        error_message,
+                        None,
+                    ));
+                }
+                (cursor.pit_id, Some(cursor.search_after))
+            }
+            None => (open_pit(&conn_config).await?, None),
+        };
+
+        let mut body = json!({
             "query": {
                 "multi_match": {
                     "query": query,
                     "fields": fields
                 },
             },
-            "size": size
+            "size": size,
+            "pit": {
+                "id": pit_id,
+                "keep_alive": &conn_config.keep_alive
+            },
+            "sort": [{ "_score": "desc" }]
         });
+        let resumed = search_after.is_some();
+        if let Some(search_after) = search_after {
+            body["search_after"] = search_after;
+        }
+
+        // build url. The index is bound to the point in time.
+        let base_url = conn_config.base_url.trim_end_matches('/');
+        let url = format!("{base_url}/_search");
 
-        let client = reqwest::Client::new();
-        let result = match api_key {
-            Some(api_key) => {
-                client
-                    .post(&url)
-                    .header("Authorization", api_key)
-                    .header("Content-Type", "application/json")
-                    .json(&body)
-                    .send()
-                    .await
+        let (mut search_response, json) =
+            execute_search(&conn_config, &url, &body)
+                .await
+                .map_err(|e| match resumed {
+                    true => cursor_error(e),
+                    false => e,
+                })?;
+
+        // the point in time id may change between requests
+        let pit_id = json["pit_id"].as_str().map(String::from).unwrap_or(pit_id);
+        let last_sort = json["hits"]["hits"]
+            .as_array()
+            .and_then(|hits| hits.last())
+            .and_then(|hit| hit.get("sort"))
+            .cloned();
+
+        match last_sort {
+            Some(search_after) if search_response.hits.hits.len() as u64 == size => {
+                let cursor = Cursor {
+                    pit_id,
+                    search_after,
+                    query,
+                };
+                search_response.cursor = Some(cursor.encode()?);
+            }
+            _ => close_pit(&conn_config, &pit_id).await,
+        }
+
+        let content = Content::json(search_response)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Perform a semantic search with k-nearest neighbors over the dense vector field of the index. Exactly one of `query` (embedded by the embedding service) and `vector` must be provided."
+    )]
+    async fn knn_search(
+        &self,
+        Parameters(KnnSearchRequest {
+            query,
+            vector,
+            k,
+            num_candidates,
+        }): Parameters<KnnSearchRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
             }
             None => {
-                client
-                    .post(&url)
-                    .header("Content-Type", "application/json")
-                    .json(&body)
-                    .send()
-                    .await
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
             }
         };
 
+        let vector = match (query, vector) {
+            (None, Some(vector)) => vector,
+            (Some(query), None) => compute_embedding(&conn_config, &query).await?,
+            (Some(_), Some(_)) => {
+                let error_message = "Only one of `query` and `vector` may be provided";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+            (None, None) => {
+                let error_message = "Either `query` or `vector` must be provided";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let k = match k {
+            Some(0) => {
+                let error_message = "`k` must be greater than 0";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+            Some(k) if k > conn_config.max_k => {
+                warn!(
+                    "`k` ({}) exceeds the maximum ({}), capping it",
+                    k, conn_config.max_k
+                );
+                conn_config.max_k
+            }
+            Some(k) => k,
+            None => conn_config.size.min(conn_config.max_k),
+        };
+        let knn = build_knn(&conn_config, vector, k, num_candidates)?;
+
+        tracing::info!("index: {}", conn_config.index);
+        tracing::info!("vector field: {}", conn_config.vector_field);
+        tracing::info!("k: {}", k);
+
+        let body = json!({
+            "knn": knn,
+            "size": k,
+            "_source": { "excludes": [&conn_config.vector_field] }
+        });
+
+        send_search(&conn_config, &body).await
+    }
+
+    #[tool(
+        description = "Perform a hybrid search combining keyword (BM25) search and k-nearest neighbors search over the dense vector field of the index. The results are fused with reciprocal rank fusion (rrf) by default, or with a weighted linear combination of the scores."
+    )]
+    async fn hybrid_search(
+        &self,
+        Parameters(HybridSearchRequest {
+            query,
+            fusion,
+            num_candidates,
+        }): Parameters<HybridSearchRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let vector = compute_embedding(&conn_config, &query).await?;
+
+        let size = conn_config.size;
+        let knn = build_knn(
+            &conn_config,
+            vector,
+            size.min(conn_config.max_k),
+            num_candidates,
+        )?;
+        let multi_match = json!({
+            "query": query,
+            "fields": &conn_config.fields
+        });
+
+        tracing::info!("index: {}", conn_config.index);
+        tracing::info!("fields: {:?}", conn_config.fields);
+        tracing::info!("vector field: {}", conn_config.vector_field);
+        tracing::info!("fusion: {:?}", fusion);
+
+        let body = match fusion.unwrap_or_default() {
+            Fusion::Rrf {
+                rank_constant,
+                rank_window_size,
+            } => {
+                let mut rrf = json!({
+                    "retrievers": [
+                        { "standard": { "query": { "multi_match": multi_match } } },
+                        { "knn": knn }
+                    ]
+                });
+                if let Some(rank_constant) = rank_constant {
+                    rrf["rank_constant"] = json!(rank_constant);
+                }
+                if let Some(rank_window_size) = rank_window_size {
+                    rrf["rank_window_size"] = json!(rank_window_size);
+                }
+                json!({
+                    "retriever": { "rrf": rrf },
+                    "size": size,
+                    "_source": { "excludes": [&conn_config.vector_field] }
+                })
+            }
+            Fusion::Linear {
+                keyword_weight,
+                vector_weight,
+            } => {
+                let mut knn = knn;
+                knn["boost"] = json!(vector_weight);
+                let mut multi_match = multi_match;
+                multi_match["boost"] = json!(keyword_weight);
+                json!({
+                    "query": { "multi_match": multi_match },
+                    "knn": knn,
+                    "size": size,
+                    "_source": { "excludes": [&conn_config.vector_field] }
+                })
+            }
+        };
+
+        send_search(&conn_config, &body).await
+    }
+
+    #[tool(
+        description = "Compute aggregations (terms, date_histogram, range, avg, sum, min, max, cardinality) over the documents in the index, optionally narrowed down by a query and a filter. Bucket aggregations may contain nested sub-aggregations."
+    )]
+    async fn aggregate(
+        &self,
+        Parameters(AggregateRequest {
+            query,
+            filter,
+            aggregations,
+        }): Parameters<AggregateRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        if aggregations.is_empty() {
+            let error_message = "At least one aggregation is required";
+            error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+
+        let aggs = build_aggregations(&aggregations).map_err(|error_message| {
+            error!(error_message);
+            McpError::new(ErrorCode::INVALID_PARAMS, error_message, None)
+        })?;
+
+        let index = &conn_config.index;
+        let fields = &conn_config.fields;
+
+        tracing::info!("index: {}", index);
+        tracing::info!("aggregations: {:?}", aggregations);
+
+        // build url
+        let base_url = conn_config.base_url.trim_end_matches('/');
+        let url = format!("{base_url}/{index}/_search");
+
+        // build query
+        let mut bool_query = serde_json::Map::new();
+        if let Some(query) = query {
+            bool_query.insert(
+                "must".to_string(),
+                json!({
+                    "multi_match": {
+                        "query": query,
+                        "fields": fields
+                    }
+                }),
+            );
+        }
+        if let Some(filter) = filter {
+            bool_query.insert("filter".to_string(), filter);
+        }
+        let query = match bool_query.is_empty() {
+            true => json!({ "match_all": {} }),
+            false => json!({ "bool": bool_query }),
+        };
+
+        let body = json!({
+            "query": query,
+            "aggs": aggs,
+            "size": 0,
+            "track_total_hits": true
+        });
+
+        let result = conn_config
+            .request(Method::POST, &url)
+            .json(&body)
+            .send()
+            .await;
+
         let response = match result {
             Ok(response) => response,
             Err(e) => {
-                let error_message = format!("Failed to search: {e}");
+                let error_message = format!("Failed to aggregate: {e}");
 
                 error!(error_message);
 
@@ -299,25 +613,36 @@ impl ElasticSearchServer {
             true => match response.json::<Value>().await {
                 Ok(json) => {
                     tracing::debug!(
-                        "search result json:\n{}",
+                        "aggregate result json:\n{}",
                         serde_json::to_string_pretty(&json).unwrap()
                     );
 
-                    let search_response: SearchResponse =
-                        serde_json::from_value(json).map_err(|e| {
-                            let error_message = format!("Failed to parse search result: {e}");
+                    let parse_error = |reason: String| {
+                        let error_message = format!("Failed to parse aggregate result: {reason}");
+
+                        error!(error_message);
+
+                        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+                    };
 
-                            error!(error_message);
+                    let aggregations = parse_aggregations(&aggregations, &json["aggregations"])
+                        .map_err(parse_error)?;
 
-                            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-                        })?;
+                    let aggregate_response = AggregateResponse {
+                        took: json["took"].as_u64().unwrap_or_default(),
+                        timed_out: json["timed_out"].as_bool().unwrap_or_default(),
+                        total: json["hits"]["total"]["value"]
+                            .as_u64()
+                            .ok_or_else(|| parse_error("missing `hits.total.value`".into()))?,
+                        aggregations,
+                    };
 
-                    let content = Content::json(search_response)?;
+                    let content = Content::json(aggregate_response)?;
 
                     Ok(CallToolResult::success(vec![content]))
                 }
                 Err(e) => {
-                    let error_message = format!("Failed to parse search result: {e}");
+                    let error_message = format!("Failed to parse aggregate result: {e}");
 
                     error!(error_message);
 
@@ -328,18 +653,152 @@ impl ElasticSearchServer {
                     ))
                 }
             },
-            false => {
-                let error_message = format!("Failed to search: {}", response.status());
+            false => Err(response_error("aggregate", response).await),
+        }
+    }
 
-                error!(error_message);
+    #[tool(
+        description = "Get the health status of the cluster, including the number of nodes and the number of active, relocating, initializing and unassigned shards"
+    )]
+    async fn cluster_health(&self) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
 
-                Err(McpError::new(
+        let json = get_json(&conn_config, "_cluster/health", "cluster health").await?;
+
+        let cluster_health: ClusterHealthResponse = serde_json::from_value(json).map_err(|e| {
+            let error_message = format!("Failed to parse cluster health: {e}");
+
+            error!(error_message);
+
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+
+        let content = Content::json(cluster_health)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Get cluster-wide statistics, including the number of indices, shards and documents, store size, node counts by role, versions, JVM heap usage and disk usage"
+    )]
+    async fn cluster_stats(&self) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
                     ErrorCode::INTERNAL_ERROR,
                     error_message,
                     None,
-                ))
+                ));
             }
-        }
+        };
+
+        let json = get_json(&conn_config, "_cluster/stats", "cluster stats").await?;
+
+        let cluster_stats: ClusterStatsResponse = serde_json::from_value(json).map_err(|e| {
+            let error_message = format!("Failed to parse cluster stats: {e}");
+
+            error!(error_message);
+
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+
+        let content = Content::json(cluster_stats)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Get information about the nodes of the cluster, including their roles, addresses, versions, attributes, operating system and JVM"
+    )]
+    async fn nodes_info(&self) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let json = get_json(&conn_config, "_nodes/_all/os,jvm", "nodes info").await?;
+
+        let nodes_info = parse_nodes_info(json).map_err(|e| {
+            let error_message = format!("Failed to parse nodes info: {e}");
+
+            error!(error_message);
+
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+
+        let content = Content::json(nodes_info)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "List cluster-level changes, e.g. index creation or mapping updates, that have not yet been executed"
+    )]
+    async fn pending_tasks(&self) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let json = get_json(&conn_config, "_cluster/pending_tasks", "pending tasks").await?;
+
+        let pending_tasks: PendingTasksResponse = serde_json::from_value(json).map_err(|e| {
+            let error_message = format!("Failed to parse pending tasks: {e}");
+
+            error!(error_message);
+
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+
+        let content = Content::json(pending_tasks)?;
+
+        Ok(CallToolResult::success(vec![content]))
     }
 }
 
@@ -357,3 +816,889 @@ impl ServerHandler for ElasticSearchServer {
         }
     }
 }
+
+/// Build the `aggs` object of an Elasticsearch search request.
+fn build_aggregations(aggregations: &[Aggregation]) -> Result<Value, String> {
+    let mut aggs = serde_json::Map::new();
+    for aggregation in aggregations {
+        if aggs.contains_key(&aggregation.name) {
+            return Err(format!("Duplicate aggregation name: {}", aggregation.name));
+        }
+
+        let mut agg = match &aggregation.kind {
+            AggregationKind::Terms { field, size } => {
+                let mut terms = json!({ "field": field });
+                if let Some(size) = size {
+                    terms["size"] = json!(size);
+                }
+                json!({ "terms": terms })
+            }
+            AggregationKind::DateHistogram {
+                field,
+                calendar_interval,
+                fixed_interval,
+                format,
+                time_zone,
+            } => {
+                let mut date_histogram = json!({ "field": field });
+                match (calendar_interval, fixed_interval) {
+                    (Some(interval), None) => date_histogram["calendar_interval"] = json!(interval),
+                    (None, Some(interval)) => date_histogram["fixed_interval"] = json!(interval),
+                    _ => {
+                        return Err(format!(
+                            "Aggregation `{}` requires exactly one of `calendar_interval` and `fixed_interval`",
+                            aggregation.name
+                        ));
+                    }
+                }
+                if let Some(format) = format {
+                    date_histogram["format"] = json!(format);
+                }
+                if let Some(time_zone) = time_zone {
+                    date_histogram["time_zone"] = json!(time_zone);
+                }
+                json!({ "date_histogram": date_histogram })
+            }
+            AggregationKind::Range { field, ranges } => {
+                if ranges.is_empty() {
+                    return Err(format!(
+                        "Aggregation `{}` requires at least one range",
+                        aggregation.name
+                    ));
+                }
+                json!({ "range": { "field": field, "ranges": ranges } })
+            }
+            AggregationKind::Avg { field } => json!({ "avg": { "field": field } }),
+            AggregationKind::Sum { field } => json!({ "sum": { "field": field } }),
+            AggregationKind::Min { field } => json!({ "min": { "field": field } }),
+            AggregationKind::Max { field } => json!({ "max": { "field": field } }),
+            AggregationKind::Cardinality { field } => json!({ "cardinality": { "field": field } }),
+        };
+
+        if !aggregation.aggregations.is_empty() {
+            if !aggregation.kind.is_bucket() {
+                return Err(format!(
+                    "Aggregation `{}` is a metric aggregation and cannot have sub-aggregations",
+                    aggregation.name
+                ));
+            }
+            // sub-aggregation results are returned next to the fields of each bucket
+            if let Some(sub_aggregation) = aggregation.aggregations.iter().find(|sub_aggregation| {
+                RESERVED_BUCKET_FIELDS.contains(&sub_aggregation.name.as_str())
+            }) {
+                return Err(format!(
+                    "Sub-aggregation name `{}` of aggregation `{}` is reserved for bucket fields",
+                    sub_aggregation.name, aggregation.name
+                ));
+            }
+            agg["aggs"] = build_aggregations(&aggregation.aggregations)?;
+        }
+
+        aggs.insert(aggregation.name.clone(), agg);
+    }
+
+    Ok(Value::Object(aggs))
+}
+
+/// Convert the `aggregations` object of an Elasticsearch search response into typed results.
+fn parse_aggregations(
+    aggregations: &[Aggregation],
+    json: &Value,
+) -> Result<Vec<AggregationResult>, String> {
+    let mut results = Vec::with_capacity(aggregations.len());
+    for aggregation in aggregations {
+        let agg = json
+            .get(&aggregation.name)
+            .ok_or_else(|| format!("missing aggregation `{}`", aggregation.name))?;
+
+        let value = match aggregation.kind.is_bucket() {
+            true => {
+                let buckets = agg["buckets"].as_array().ok_or_else(|| {
+                    format!("missing buckets of aggregation `{}`", aggregation.name)
+                })?;
+
+                let mut parsed = Vec::with_capacity(buckets.len());
+                for bucket in buckets {
+                    parsed.push(Bucket {
+                        key: bucket["key"].clone(),
+                        key_as_string: bucket["key_as_string"].as_str().map(String::from),
+                        doc_count: bucket["doc_count"].as_u64().ok_or_else(|| {
+                            format!("missing doc_count in aggregation `{}`", aggregation.name)
+                        })?,
+                        aggregations: parse_aggregations(&aggregation.aggregations, bucket)?,
+                    });
+                }
+
+                AggregationValue::Buckets { buckets: parsed }
+            }
+            false => AggregationValue::Metric {
+                value: agg["value"].as_f64(),
+            },
+        };
+
+        results.push(AggregationResult {
+            name: aggregation.name.clone(),
+            value,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Convert a nodes info response, where the nodes are keyed by node id, into a list of nodes.
+fn parse_nodes_info(json: Value) -> Result<NodesInfoResponse, serde_json::Error> {
+    let cluster_name = json["cluster_name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let mut nodes = Vec::new();
+    if let Some(map) = json["nodes"].as_object() {
+        for (id, node) in map {
+            let mut node: NodeInfo = serde_json::from_value(node.clone())?;
+            node.id = id.clone();
+            nodes.push(node);
+        }
+    }
+
+    Ok(NodesInfoResponse {
+        cluster_name,
+        nodes,
+    })
+}
+
+/// Send a search request to the configured index and return the hits as the tool result.
+async fn send_search(
+    conn_config: &ConnectionConfig,
+    body: &Value,
+) -> Result<CallToolResult, McpError> {
+    // build url
+    let base_url = conn_config.base_url.trim_end_matches('/');
+    let url = format!("{base_url}/{}/_search", conn_config.index);
+
+    let (search_response, _) = execute_search(conn_config, &url, body).await?;
+
+    let content = Content::json(search_response)?;
+
+    Ok(CallToolResult::success(vec![content]))
+}
+
+/// Send a search request and parse the response. The raw response is returned alongside the
+/// parsed one, since it carries fields not exposed to the client, e.g. the sort values of the hits.
+async fn execute_search(
+    conn_config: &ConnectionConfig,
+    url: &str,
+    body: &Value,
+) -> Result<(SearchResponse, Value), McpError> {
+    let result = conn_config
+        .request(Method::POST, url)
+        .json(body)
+        .send()
+        .await;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let error_message = format!("Failed to search: {e}");
+
+            error!(error_message);
+
+            return Err(McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                error_message,
+                None,
+            ));
+        }
+    };
+
+    match response.status().is_success() {
+        true => match response.json::<Value>().await {
+            Ok(json) => {
+                tracing::debug!(
+                    "search result json:\n{}",
+                    serde_json::to_string_pretty(&json).unwrap()
+                );
+
+                let search_response: SearchResponse = serde_json::from_value(json.clone())
+                    .map_err(|e| {
+                        let error_message = format!("Failed to parse search result: {e}");
+
+                        error!(error_message);
+
+                        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+                    })?;
+
+                Ok((search_response, json))
+            }
+            Err(e) => {
+                let error_message = format!("Failed to parse search result: {e}");
+
+                error!(error_message);
+
+                Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ))
+            }
+        },
+        false => Err(response_error("search", response).await),
+    }
+}
+
+/// Build the `knn` clause of a search request. `k` must be between 1 and
+/// [`MAX_NUM_CANDIDATES`], and `num_candidates`, if set, between `k` and [`MAX_NUM_CANDIDATES`].
+fn build_knn(
+    conn_config: &ConnectionConfig,
+    vector: Vec<f64>,
+    k: u64,
+    num_candidates: Option<u64>,
+) -> Result<Value, McpError> {
+    let num_candidates = match num_candidates {
+        Some(num_candidates) if num_candidates < k || num_candidates > MAX_NUM_CANDIDATES => {
+            let error_message =
+                format!("`num_candidates` must be between `k` ({k}) and {MAX_NUM_CANDIDATES}");
+            error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+        Some(num_candidates) => num_candidates,
+        // default to the same heuristic as Elasticsearch: 1.5 * k, capped at 10000
+        None => (k + k / 2).min(MAX_NUM_CANDIDATES),
+    };
+
+    Ok(json!({
+        "field": &conn_config.vector_field,
+        "query_vector": vector,
+        "k": k,
+        "num_candidates": num_candidates
+    }))
+}
+
+/// Compute the embedding of the query with the configured embedding service.
+async fn compute_embedding(
+    conn_config: &ConnectionConfig,
+    query: impl AsRef<str>,
+) -> Result<Vec<f64>, McpError> {
+    match &conn_config.embedding_service {
+        Some(config) => {
+            let embedding_service_url = format!("{}/embeddings", config.url.trim_end_matches('/'));
+
+            // create a embedding request
+            let embedding_request = EmbeddingRequest {
+                model: None,
+                input: InputText::String(query.as_ref().to_string()),
+                encoding_format: None,
+                user: None,
+            };
+
+            let mut request = reqwest::Client::new()
+                .post(&embedding_service_url)
+                .json(&embedding_request);
+            if let Some(api_key) = &config.api_key {
+                request = request.header(AUTHORIZATION, api_key);
+            }
+
+            let response = request.send().await.map_err(|e| {
+                let err_msg = format!("Failed to send the embedding request: {e}");
+                error!("{}", err_msg);
+                McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None)
+            })?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let err_msg = format!("Failed to compute the embedding. Status: {status}");
+                error!("{}", err_msg);
+                return Err(McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None));
+            }
+
+            // parse the response
+            let embedding_response = response.json::<EmbeddingsResponse>().await.map_err(|e| {
+                let err_msg = format!("Failed to parse embeddings response: {e}");
+                error!("{}", err_msg);
+                McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None)
+            })?;
+
+            let embedding = embedding_response.data.first().ok_or_else(|| {
+                let err_msg = "No embeddings returned";
+                error!("{}", err_msg);
+                McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None)
+            })?;
+
+            Ok(embedding.embedding.to_vec())
+        }
+        None => {
+            let error_message = "Embedding service is not configured. Start the server with `--embedding-service` to enable semantic search.";
+            error!("{}", error_message);
+            Err(McpError::new(
+                ErrorCode::INVALID_REQUEST,
+                error_message,
+                None,
+            ))
+        }
+    }
+}
+
+/// Open a point in time on the configured index.
+async fn open_pit(conn_config: &ConnectionConfig) -> Result<String, McpError> {
+    let base_url = conn_config.base_url.trim_end_matches('/');
+    let url = format!(
+        "{base_url}/{}/_pit?keep_alive={}",
+        conn_config.index, conn_config.keep_alive
+    );
+
+    let response = conn_config
+        .request(Method::POST, &url)
+        .send()
+        .await
+        .map_err(|e| {
+            let error_message = format!("Failed to open point in time: {e}");
+            error!(error_message);
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+
+    if !response.status().is_success() {
+        return Err(response_error("open point in time", response).await);
+    }
+
+    let json = response.json::<Value>().await.map_err(|e| {
+        let error_message = format!("Failed to parse point in time: {e}");
+        error!(error_message);
+        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+    })?;
+
+    match json["id"].as_str() {
+        Some(id) => Ok(id.to_string()),
+        None => {
+            let error_message = "Failed to parse point in time: missing `id`";
+            error!(error_message);
+            Err(McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                error_message,
+                None,
+            ))
+        }
+    }
+}
+
+/// Close a point in time. Failures are only logged, since the point in time expires anyway.
+async fn close_pit(conn_config: &ConnectionConfig, pit_id: &str) {
+    let base_url = conn_config.base_url.trim_end_matches('/');
+    let url = format!("{base_url}/_pit");
+
+    let result = conn_config
+        .request(Method::DELETE, &url)
+        .json(&json!({ "id": pit_id }))
+        .send()
+        .await;
+
+    match result {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!("Failed to close point in time: {}", response.status()),
+        Err(e) => warn!("Failed to close point in time: {e}"),
+    }
+}
+
+/// Report a search resumed from a cursor whose point in time has expired, or is unknown to the
+/// cluster, as invalid params, since the client has to search again without the cursor. Other
+/// errors are returned as is.
+fn cursor_error(e: McpError) -> McpError {
+    let expired = e.data.as_ref().is_some_and(|data| {
+        data["status"] == 404 || data["body"]["error"]["type"] == "search_context_missing_exception"
+    });
+    if !expired {
+        return e;
+    }
+
+    let error_message = "The cursor has expired, search again without a cursor";
+    error!(error_message);
+    McpError::new(ErrorCode::INVALID_PARAMS, error_message, e.data)
+}
+
+/// Opaque pagination cursor returned to the client.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    /// id of the point in time
+    pit_id: String,
+    /// sort values of the last hit of the previous page
+    search_after: Value,
+    /// query of the previous page
+    query: String,
+}
+impl Cursor {
+    fn encode(&self) -> Result<String, McpError> {
+        let json = serde_json::to_vec(self).map_err(|e| {
+            let error_message = format!("Failed to encode cursor: {e}");
+            error!(error_message);
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+
+        Ok(URL_SAFE_NO_PAD.encode(json))
+    }
+
+    fn decode(cursor: &str) -> Result<Self, McpError> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| {
+                let error_message = "Invalid cursor";
+                error!(error_message);
+                McpError::new(ErrorCode::INVALID_PARAMS, error_message, None)
+            })
+    }
+}
+
+/// Send a GET request to the given path of the Elasticsearch server and return the response body.
+async fn get_json(
+    conn_config: &ConnectionConfig,
+    path: &str,
+    what: &str,
+) -> Result<Value, McpError> {
+    let base_url = conn_config.base_url.trim_end_matches('/');
+    let url = format!("{base_url}/{path}");
+
+    let response = conn_config
+        .request(Method::GET, &url)
+        .send()
+        .await
+        .map_err(|e| {
+            let error_message = format!("Failed to get {what}: {e}");
+            error!(error_message);
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+
+    if !response.status().is_success() {
+        return Err(response_error(&format!("get {what}"), response).await);
+    }
+
+    response.json::<Value>().await.map_err(|e| {
+        let error_message = format!("Failed to parse {what}: {e}");
+        error!(error_message);
+        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+    })
+}
+
+/// Build the error of a request that Elasticsearch rejected, with the reason given in the response
+/// body, e.g. `{"error": {"type": "index_not_found_exception", "reason": "no such index [docs]"}}`.
+/// The status and the body are passed on in the error data.
+async fn response_error(action: &str, response: reqwest::Response) -> McpError {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+
+    let body = serde_json::from_str::<Value>(&text).unwrap_or(Value::String(text));
+    let reason = match &body["error"] {
+        Value::Object(error) => error.get("reason").and_then(Value::as_str),
+        Value::String(error) => Some(error.as_str()),
+        _ => None,
+    };
+    let detail = match (reason, &body) {
+        (Some(reason), _) => reason.to_string(),
+        (None, Value::String(text)) if !text.is_empty() => text.clone(),
+        _ => status.to_string(),
+    };
+    let error_message = format!("Failed to {action}: {detail} (HTTP {})", status.as_u16());
+
+    error!(error_message);
+
+    McpError::new(
+        ErrorCode::INTERNAL_ERROR,
+        error_message,
+        Some(json!({ "status": status.as_u16(), "body": body })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Auth;
+    use axum::{Router, http::StatusCode};
+
+    fn conn_config(base_url: String) -> ConnectionConfig {
+        ConnectionConfig {
+            base_url,
+            auth: Auth::None,
+            client: reqwest::Client::new(),
+            index: "docs".to_string(),
+            fields: vec!["title".to_string(), "content".to_string()],
+            size: 10,
+            keep_alive: "1m".to_string(),
+            vector_field: "embedding".to_string(),
+            max_k: 100,
+            embedding_service: None,
+        }
+    }
+
+    fn aggregations(value: Value) -> Vec<Aggregation> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn build_nested_aggregations() {
+        let aggregations = aggregations(json!([
+            {
+                "name": "per_week",
+                "type": "date_histogram",
+                "field": "created_at",
+                "calendar_interval": "week",
+                "aggregations": [
+                    { "name": "by_status", "type": "terms", "field": "status", "size": 5 }
+                ]
+            },
+            { "name": "avg_price", "type": "avg", "field": "price" }
+        ]));
+
+        assert_eq!(
+            build_aggregations(&aggregations).unwrap(),
+            json!({
+                "per_week": {
+                    "date_histogram": { "field": "created_at", "calendar_interval": "week" },
+                    "aggs": {
+                        "by_status": { "terms": { "field": "status", "size": 5 } }
+                    }
+                },
+                "avg_price": { "avg": { "field": "price" } }
+            })
+        );
+    }
+
+    #[test]
+    fn reject_invalid_aggregations() {
+        for aggregations in [
+            // duplicate name
+            json!([
+                { "name": "a", "type": "avg", "field": "price" },
+                { "name": "a", "type": "sum", "field": "price" }
+            ]),
+            // both intervals
+            json!([{
+                "name": "a",
+                "type": "date_histogram",
+                "field": "created_at",
+                "calendar_interval": "week",
+                "fixed_interval": "7d"
+            }]),
+            // no range
+            json!([{ "name": "a", "type": "range", "field": "price", "ranges": [] }]),
+            // sub-aggregation of a metric aggregation
+            json!([{
+                "name": "a",
+                "type": "avg",
+                "field": "price",
+                "aggregations": [{ "name": "b", "type": "sum", "field": "price" }]
+            }]),
+            // sub-aggregation names colliding with bucket fields
+            json!([{
+                "name": "a",
+                "type": "terms",
+                "field": "status",
+                "aggregations": [{ "name": "doc_count", "type": "sum", "field": "price" }]
+            }]),
+            json!([{
+                "name": "a",
+                "type": "terms",
+                "field": "status",
+                "aggregations": [{ "name": "key", "type": "cardinality", "field": "user" }]
+            }]),
+        ] {
+            assert!(
+                build_aggregations(&self::aggregations(aggregations.clone())).is_err(),
+                "{aggregations}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_nested_aggregations() {
+        let aggregations = aggregations(json!([
+            {
+                "name": "per_week",
+                "type": "date_histogram",
+                "field": "created_at",
+                "calendar_interval": "week",
+                "aggregations": [
+                    { "name": "by_status", "type": "terms", "field": "status" },
+                    { "name": "users", "type": "cardinality", "field": "user" }
+                ]
+            },
+            { "name": "avg_price", "type": "avg", "field": "price" }
+        ]));
+        let response = json!({
+            "per_week": {
+                "buckets": [{
+                    "key": 1719792000000u64,
+                    "key_as_string": "2024-07-01",
+                    "doc_count": 3,
+                    "by_status": {
+                        "doc_count_error_upper_bound": 0,
+                        "sum_other_doc_count": 0,
+                        "buckets": [
+                            { "key": "open", "doc_count": 2 },
+                            { "key": "closed", "doc_count": 1 }
+                        ]
+                    },
+                    "users": { "value": 2 }
+                }]
+            },
+            "avg_price": { "value": null }
+        });
+
+        let results = parse_aggregations(&aggregations, &response).unwrap();
+        assert_eq!(
+            serde_json::to_value(&results).unwrap(),
+            json!([
+                {
+                    "name": "per_week",
+                    "type": "buckets",
+                    "buckets": [{
+                        "key": 1719792000000u64,
+                        "key_as_string": "2024-07-01",
+                        "doc_count": 3,
+                        "aggregations": [
+                            {
+                                "name": "by_status",
+                                "type": "buckets",
+                                "buckets": [
+                                    { "key": "open", "doc_count": 2 },
+                                    { "key": "closed", "doc_count": 1 }
+                                ]
+                            },
+                            { "name": "users", "type": "metric", "value": 2.0 }
+                        ]
+                    }]
+                },
+                { "name": "avg_price", "type": "metric", "value": null }
+            ])
+        );
+
+        // missing aggregation or bucket field
+        assert!(parse_aggregations(&aggregations, &json!({})).is_err());
+        assert!(
+            parse_aggregations(
+                &aggregations,
+                &json!({ "per_week": { "buckets": [{ "key": 1 }] }, "avg_price": {} })
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_cluster_health_and_pending_tasks() {
+        let health: ClusterHealthResponse = serde_json::from_value(json!({
+            "cluster_name": "docker-cluster",
+            "status": "yellow",
+            "timed_out": false,
+            "number_of_nodes": 1,
+            "number_of_data_nodes": 1,
+            "active_primary_shards": 5,
+            "active_shards": 5,
+            "relocating_shards": 0,
+            "initializing_shards": 0,
+            "unassigned_shards": 1,
+            "unassigned_primary_shards": 0,
+            "delayed_unassigned_shards": 0,
+            "number_of_pending_tasks": 1,
+            "number_of_in_flight_fetch": 0,
+            "task_max_waiting_in_queue_millis": 12,
+            "active_shards_percent_as_number": 83.33333333333334
+        }))
+        .unwrap();
+        assert_eq!(health.status, "yellow");
+        assert_eq!(health.unassigned_shards, 1);
+
+        let pending_tasks: PendingTasksResponse = serde_json::from_value(json!({
+            "tasks": [{
+                "insert_order": 101,
+                "priority": "URGENT",
+                "source": "create-index [foo_9], cause [api]",
+                "executing": true,
+                "time_in_queue_millis": 86,
+                "time_in_queue": "86ms"
+            }]
+        }))
+        .unwrap();
+        assert_eq!(pending_tasks.tasks[0].priority, "URGENT");
+    }
+
+    #[test]
+    fn parse_cluster_stats() {
+        let stats: ClusterStatsResponse = serde_json::from_value(json!({
+            "_nodes": { "total": 1, "successful": 1, "failed": 0 },
+            "cluster_name": "docker-cluster",
+            "cluster_uuid": "Z6ZL0ob5T8CTHmXHnD6pVQ",
+            "timestamp": 1719820800000u64,
+            "status": "green",
+            "indices": {
+                // no shard statistics when the cluster has no index
+                "count": 0,
+                "shards": {},
+                "docs": { "count": 0, "deleted": 0 },
+                "store": { "size_in_bytes": 0, "reserved_in_bytes": 0 }
+            },
+            "nodes": {
+                "count": { "total": 1, "data": 1, "master": 1, "ingest": 1 },
+                "versions": ["8.14.1"],
+                "jvm": {
+                    "max_uptime_in_millis": 3600000,
+                    "mem": { "heap_used_in_bytes": 268435456, "heap_max_in_bytes": 1073741824 },
+                    "threads": 80
+                },
+                "fs": {
+                    "total_in_bytes": 100,
+                    "free_in_bytes": 60,
+                    "available_in_bytes": 50
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(stats.indices.shards.total, 0);
+        assert_eq!(stats.nodes.count["data"], 1);
+        assert_eq!(stats.nodes.jvm.mem.heap_max_in_bytes, 1073741824);
+    }
+
+    #[test]
+    fn parse_nodes_info_keyed_by_id() {
+        let nodes_info = parse_nodes_info(json!({
+            "_nodes": { "total": 2, "successful": 2, "failed": 0 },
+            "cluster_name": "docker-cluster",
+            "nodes": {
+                "b2Nv": {
+                    "name": "es02",
+                    "transport_address": "172.18.0.3:9300",
+                    "host": "172.18.0.3",
+                    "ip": "172.18.0.3",
+                    "version": "8.14.1",
+                    "roles": ["data"],
+                    "os": { "pretty_name": "Ubuntu 22.04.4 LTS", "arch": "amd64", "available_processors": 4 }
+                },
+                "a1Mx": {
+                    "name": "es01",
+                    "transport_address": "172.18.0.2:9300",
+                    "host": "172.18.0.2",
+                    "ip": "172.18.0.2",
+                    "version": "8.14.1",
+                    "roles": ["data", "master"],
+                    "attributes": { "ml.machine_memory": "8335581184" },
+                    "jvm": { "version": "22.0.1", "mem": { "heap_max_in_bytes": 1073741824 } }
+                }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(nodes_info.cluster_name, "docker-cluster");
+        let ids: Vec<_> = nodes_info
+            .nodes
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect();
+        assert_eq!(ids, ["a1Mx", "b2Nv"]);
+        assert_eq!(nodes_info.nodes[0].name, "es01");
+        assert_eq!(
+            nodes_info.nodes[1]
+                .os
+                .as_ref()
+                .unwrap()
+                .available_processors,
+            Some(4)
+        );
+
+        // a node without its required fields
+        assert!(parse_nodes_info(json!({ "nodes": { "a1Mx": { "name": "es01" } } })).is_err());
+    }
+
+    async fn mock_server(status: StatusCode, body: &'static str) -> ConnectionConfig {
+        let app = Router::new().fallback(move || async move { (status, body) });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        conn_config(format!("http://{addr}"))
+    }
+
+    #[tokio::test]
+    async fn error_reason_is_reported() {
+        let conn_config = mock_server(
+            StatusCode::BAD_REQUEST,
+            r#"{"error": {"type": "parsing_exception", "reason": "unknown query [mach]"}, "status": 400}"#,
+        )
+        .await;
+
+        let error = get_json(&conn_config, "_cluster/health", "cluster health")
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(
+            error.message,
+            "Failed to get cluster health: unknown query [mach] (HTTP 400)"
+        );
+        assert_eq!(
+            error.data.unwrap()["body"]["error"]["type"],
+            "parsing_exception"
+        );
+
+        // a body without an Elasticsearch error
+        let conn_config = mock_server(StatusCode::BAD_GATEWAY, "").await;
+
+        let error = open_pit(&conn_config).await.unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Failed to open point in time: 502 Bad Gateway (HTTP 502)"
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_cursor_is_invalid_params() {
+        let search = |conn_config: ConnectionConfig| async move {
+            let url = format!("{}/_search", conn_config.base_url);
+            execute_search(&conn_config, &url, &json!({}))
+                .await
+                .unwrap_err()
+        };
+
+        let conn_config = mock_server(
+            StatusCode::NOT_FOUND,
+            r#"{"error": {"type": "search_context_missing_exception", "reason": "No search context found for id [42]"}, "status": 404}"#,
+        )
+        .await;
+
+        let error = cursor_error(search(conn_config).await);
+
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(
+            error.message,
+            "The cursor has expired, search again without a cursor"
+        );
+
+        // other errors are not the cursor's fault
+        let conn_config = mock_server(StatusCode::SERVICE_UNAVAILABLE, "").await;
+
+        let error = cursor_error(search(conn_config).await);
+
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    }
+
+    #[test]
+    fn knn_num_candidates_is_bounded() {
+        let conn_config = conn_config("http://127.0.0.1:9200".to_string());
+        let num_candidates = |k, num_candidates| {
+            build_knn(&conn_config, vec![0.5, 1.0], k, num_candidates)
+                .map(|knn| knn["num_candidates"].as_u64().unwrap())
+        };
+
+        assert_eq!(num_candidates(10, None).unwrap(), 15);
+        assert_eq!(num_candidates(10_000, None).unwrap(), 10_000);
+        assert_eq!(num_candidates(10, Some(10)).unwrap(), 10);
+        assert_eq!(num_candidates(10, Some(10_000)).unwrap(), 10_000);
+        for invalid in [9, 10_001] {
+            assert_eq!(
+                num_candidates(10, Some(invalid)).unwrap_err().code,
+                ErrorCode::INVALID_PARAMS
+            );
+        }
+    }
+}
@@ -7,6 +7,7 @@ use rmcp::transport::{
     sse_server::SseServer,
     streamable_http_server::{StreamableHttpService, session::local::LocalSessionManager},
 };
+use std::{env, path::PathBuf};
 use tokio::sync::RwLock;
 use tracing_subscriber::{self, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -35,6 +36,43 @@ struct Args {
     /// Maximum number of query results to return
     #[arg(long, default_value = "10")]
     size: u64,
+    /// How long the point in time of a paginated search is kept alive between pages
+    #[arg(long, default_value = "1m")]
+    keep_alive: String,
+    /// API key used to authenticate with the Elasticsearch server. Falls back to the
+    /// `ES_API_KEY` environment variable.
+    #[arg(long)]
+    api_key: Option<String>,
+    /// Username used for basic authentication. Falls back to the `ES_USERNAME` environment
+    /// variable.
+    #[arg(long)]
+    username: Option<String>,
+    /// Password used for basic authentication. Falls back to the `ES_PASSWORD` environment
+    /// variable.
+    #[arg(long)]
+    password: Option<String>,
+    /// Bearer token used to authenticate with the Elasticsearch server. Falls back to the
+    /// `ES_BEARER_TOKEN` environment variable.
+    #[arg(long)]
+    bearer_token: Option<String>,
+    /// Path to a PEM-encoded CA certificate bundle used to verify the Elasticsearch server
+    #[arg(long)]
+    ca_cert: Option<PathBuf>,
+    /// Path to a PEM-encoded client certificate used for mutual TLS
+    #[arg(long, requires = "client_key")]
+    client_cert: Option<PathBuf>,
+    /// Path to the PEM-encoded private key of the client certificate
+    #[arg(long, requires = "client_cert")]
+    client_key: Option<PathBuf>,
+    /// The base URL of the embedding server used by the knn and hybrid search, e.g., "https://api.openai.com/v1"
+    #[arg(long)]
+    embedding_service: Option<String>,
+    /// Name of the dense vector field used by the knn and hybrid search
+    #[arg(long, default_value = "embedding")]
+    vector_field: String,
+    /// Upper bound for the `k` of a knn search, at most 10000
+    #[arg(long, default_value = "100")]
+    max_k: u64,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -53,10 +91,41 @@ async fn main() -> anyhow::Result<()> {
         .with(tracing_subscriber::fmt::layer().with_target(true))
         .init();
 
-    let api_key = match std::env::var("ES_API_KEY") {
-        Ok(api_key) => format!("ApiKey {api_key}"),
-        Err(_) => {
-            let err_message = "The environment variable `ES_API_KEY` is not set";
+    let args = Args::parse();
+
+    if args.max_k == 0 || args.max_k > elastic::MAX_NUM_CANDIDATES {
+        anyhow::bail!(
+            "--max-k must be between 1 and {}",
+            elastic::MAX_NUM_CANDIDATES
+        );
+    }
+
+    let auth = match (
+        args.api_key.or_else(|| env::var("ES_API_KEY").ok()),
+        args.username.or_else(|| env::var("ES_USERNAME").ok()),
+        args.password.or_else(|| env::var("ES_PASSWORD").ok()),
+        args.bearer_token
+            .or_else(|| env::var("ES_BEARER_TOKEN").ok()),
+    ) {
+        (None, None, None, None) => {
+            tracing::warn!(
+                "No credentials provided, connecting to Elasticsearch without authentication"
+            );
+            Auth::None
+        }
+        (Some(api_key), None, None, None) => Auth::ApiKey(api_key),
+        (None, Some(username), password, None) => Auth::Basic { username, password },
+        (None, None, None, Some(token)) => Auth::Bearer(token),
+        (_, None, Some(_), _) => {
+            let err_message = "A password was provided without a username";
+
+            tracing::error!("{}", err_message);
+
+            return Err(anyhow::anyhow!(err_message));
+        }
+        _ => {
+            let err_message =
+                "Only one of API key, username/password and bearer token can be provided";
 
             tracing::error!("{}", err_message);
 
@@ -64,14 +133,30 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    let args = Args::parse();
+    // build http client
+    let client = cardea_common::build_http_client(
+        args.ca_cert.as_deref(),
+        args.client_cert.as_deref(),
+        args.client_key.as_deref(),
+    )?;
+
+    // parse embedding service api key
+    let embedding_service = args.embedding_service.map(|url| ServiceConfig {
+        url,
+        api_key: env::var("EMBEDDING_SERVICE_API_KEY").ok(),
+    });
 
     let connection_config = ConnectionConfig {
         base_url: args.base_url,
-        api_key: Some(api_key),
+        auth,
+        client,
         index: args.index,
         fields: args.fields,
         size: args.size,
+        keep_alive: args.keep_alive,
+        vector_field: args.vector_field,
+        max_k: args.max_k,
+        embedding_service,
     };
 
     CONNECTION_CONFIG
@@ -110,11 +195,97 @@ async fn main() -> anyhow::Result<()> {
 #[derive(Debug, Clone)]
 pub struct ConnectionConfig {
     pub base_url: String,
-    pub api_key: Option<String>,
+    /// credentials used to authenticate with the Elasticsearch server
+    pub auth: Auth,
+    /// http client configured with the TLS options
+    pub client: reqwest::Client,
     /// index name
     pub index: String,
     /// name of fields to search
     pub fields: Vec<String>,
     /// number of results to return
     pub size: u64,
+    /// keep alive of the point in time used for pagination, e.g. `1m`
+    pub keep_alive: String,
+    /// name of the dense vector field
+    pub vector_field: String,
+    /// upper bound for the number of nearest neighbors of a knn search
+    pub max_k: u64,
+    /// embedding service used to embed the query of the knn and hybrid search
+    pub embedding_service: Option<ServiceConfig>,
+}
+impl ConnectionConfig {
+    /// Create a request to the Elasticsearch server with the configured credentials.
+    pub fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.auth {
+            Auth::None => request,
+            Auth::ApiKey(api_key) => request.header("Authorization", format!("ApiKey {api_key}")),
+            Auth::Basic { username, password } => request.basic_auth(username, password.as_ref()),
+            Auth::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Auth {
+    /// no authentication, e.g. for clusters with security disabled
+    None,
+    /// `Authorization: ApiKey <key>`
+    ApiKey(String),
+    /// `Authorization: Basic <base64(username:password)>`
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    /// `Authorization: Bearer <token>`
+    Bearer(String),
+}
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never print the credentials
+        match self {
+            Auth::None => write!(f, "None"),
+            Auth::ApiKey(_) => write!(f, "ApiKey(***)"),
+            Auth::Basic { username, .. } => write!(f, "Basic({username}:***)"),
+            Auth::Bearer(_) => write!(f, "Bearer(***)"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ServiceConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+}
+impl std::fmt::Debug for ServiceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never print the api key
+        f.debug_struct("ServiceConfig")
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_hides_credentials() {
+        let service = ServiceConfig {
+            url: "https://api.openai.com/v1".to_string(),
+            api_key: Some("sk-secret".to_string()),
+        };
+        let auth = Auth::Basic {
+            username: "elastic".to_string(),
+            password: Some("secret".to_string()),
+        };
+
+        for debug in [format!("{service:?}"), format!("{auth:?}")] {
+            assert!(!debug.contains("secret"), "{debug}");
+        }
+        assert!(format!("{service:?}").contains("https://api.openai.com/v1"));
+    }
 }
@@ -1,15 +1,45 @@
 use endpoints::keyword_search::{
     DocumentInput, DocumentResult, IndexResponse, QueryResponse, SearchHit,
 };
-use rmcp::{model::CallToolResult, schemars};
+use rmcp::{
+    model::{CallToolResult, ErrorCode},
+    schemars,
+};
 use serde::{Deserialize, Serialize};
 
+/// Error code returned when the kw-search-server is unreachable, rate limits the request or fails
+/// with a 5xx status.
+/// Invalid requests rejected by the kw-search-server are reported as `ErrorCode::INVALID_PARAMS`.
+pub const BACKEND_UNAVAILABLE: ErrorCode = ErrorCode(-32001);
+
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct CreateIndexRequest {
     #[schemars(description = "The name of the index to create")]
     pub index: String,
     #[schemars(description = "The documents to index")]
+    #[serde(default)]
     pub documents: Vec<KwDocumentInput>,
+    #[schemars(
+        description = "Paths of files or directories to ingest, relative to the ingestion root configured on the server. Supported files: .txt, .md, .html, .htm, .pdf and .jsonl (one `{\"content\": ..., \"title\": ...}` document per line)"
+    )]
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[schemars(
+        description = "How to split the documents into chunks. Documents are not split by default"
+    )]
+    #[serde(default)]
+    pub chunking: Option<ChunkingOptions>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ChunkingOptions {
+    #[schemars(description = "The maximum number of characters of a chunk")]
+    pub chunk_size: usize,
+    #[schemars(
+        description = "The number of characters shared by consecutive chunks. Must be smaller than `chunk_size`. Defaults to 0"
+    )]
+    #[serde(default)]
+    pub chunk_overlap: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
@@ -81,6 +111,16 @@ impl From<DocumentResult> for KwDocumentResult {
 pub struct SearchDocumentsRequest {
     #[schemars(description = "The query to search for")]
     pub query: String,
+    #[schemars(
+        description = "The name of the index to search. Defaults to the index configured on the server"
+    )]
+    #[serde(default)]
+    pub index: Option<String>,
+    #[schemars(
+        description = "Maximum number of results to return. Defaults to the limit configured on the server and is capped by the server's maximum"
+    )]
+    #[serde(default)]
+    pub top_k: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -124,3 +164,124 @@ impl From<SearchHit> for KwSearchHit {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ListIndexesResponse {
+    #[schemars(description = "The indexes in the KeywordSearch database")]
+    pub indexes: Vec<KwIndexInfo>,
+}
+impl From<CallToolResult> for ListIndexesResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<ListIndexesResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct KwIndexInfo {
+    #[schemars(description = "The name of the index")]
+    pub name: String,
+    #[schemars(description = "The number of documents in the index")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_count: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DeleteIndexRequest {
+    #[schemars(description = "The name of the index to delete")]
+    pub index: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DeleteIndexResponse {
+    #[schemars(description = "The name of the index")]
+    pub index: String,
+    #[schemars(description = "Whether the index was deleted")]
+    pub deleted: bool,
+}
+impl From<CallToolResult> for DeleteIndexResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<DeleteIndexResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AddDocumentsRequest {
+    #[schemars(description = "The name of the existing index to add the documents to")]
+    pub index: String,
+    #[schemars(description = "The documents to add")]
+    #[serde(default)]
+    pub documents: Vec<KwDocumentInput>,
+    #[schemars(
+        description = "Paths of files or directories to ingest, relative to the ingestion root configured on the server. Supported files: .txt, .md, .html, .htm, .pdf and .jsonl (one `{\"content\": ..., \"title\": ...}` document per line)"
+    )]
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[schemars(
+        description = "How to split the documents into chunks. Documents are not split by default"
+    )]
+    #[serde(default)]
+    pub chunking: Option<ChunkingOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AddDocumentsResponse {
+    #[schemars(description = "The name of the index")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_name: Option<String>,
+    #[schemars(description = "The results of the indexing operation")]
+    pub results: Vec<KwDocumentResult>,
+}
+impl From<IndexResponse> for AddDocumentsResponse {
+    fn from(value: IndexResponse) -> Self {
+        Self {
+            index_name: value.index_name,
+            results: value.results.into_iter().map(|r| r.into()).collect(),
+        }
+    }
+}
+impl From<CallToolResult> for AddDocumentsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<AddDocumentsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DeleteDocumentsRequest {
+    #[schemars(description = "The name of the index to delete the documents from")]
+    pub index: String,
+    #[schemars(description = "The titles of the documents to delete")]
+    pub titles: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DeleteDocumentsResponse {
+    #[schemars(description = "The name of the index")]
+    pub index: String,
+    #[schemars(description = "The number of deleted documents")]
+    pub deleted: u64,
+}
+impl From<CallToolResult> for DeleteDocumentsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<DeleteDocumentsResponse>(&content).unwrap()
+    }
+}
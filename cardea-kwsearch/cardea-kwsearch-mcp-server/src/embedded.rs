@@ -0,0 +1,515 @@
+use cardea_kwsearch_mcp_common::KwIndexInfo;
+use endpoints::keyword_search::{
+    DocumentInput, DocumentResult, IndexResponse, QueryResponse, SearchHit,
+};
+use rmcp::{ErrorData as McpError, model::ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+};
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+/// BM25 term frequency saturation
+const K1: f64 = 1.2;
+/// BM25 document length normalization
+const B: f64 = 0.75;
+
+/// Keyword search backend that keeps BM25 inverted indexes on disk, one JSON file per index.
+pub struct EmbeddedStore {
+    dir: PathBuf,
+    indexes: RwLock<HashMap<String, InvertedIndex>>,
+}
+impl fmt::Debug for EmbeddedStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmbeddedStore")
+            .field("dir", &self.dir)
+            .finish_non_exhaustive()
+    }
+}
+impl EmbeddedStore {
+    /// Open the store in the given directory, loading the indexes it contains.
+    pub async fn open(dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let mut indexes = HashMap::new();
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let bytes = tokio::fs::read(&path).await?;
+            let index: InvertedIndex = serde_json::from_slice(&bytes)
+                .map_err(|e| anyhow::anyhow!("Failed to load index {}: {e}", path.display()))?;
+            info!(
+                "Loaded index {} ({} documents)",
+                name,
+                index.documents.len()
+            );
+            indexes.insert(name.to_string(), index);
+        }
+
+        Ok(Self {
+            dir,
+            indexes: RwLock::new(indexes),
+        })
+    }
+
+    pub async fn create_index(
+        &self,
+        name: String,
+        documents: Vec<DocumentInput>,
+    ) -> Result<IndexResponse, McpError> {
+        validate_index_name(&name)?;
+
+        let mut indexes = self.indexes.write().await;
+        if indexes.contains_key(&name) {
+            return Err(invalid_params(format!(
+                "Index `{name}` already exists. Use `add_documents` to add documents to it"
+            )));
+        }
+
+        let mut index = InvertedIndex::default();
+        let results = documents.into_iter().map(|d| index.add(d)).collect();
+        self.save(&name, &index).await?;
+        indexes.insert(name.clone(), index);
+
+        Ok(IndexResponse {
+            results,
+            index_name: Some(name),
+            download_url: None,
+        })
+    }
+
+    pub async fn add_documents(
+        &self,
+        name: String,
+        documents: Vec<DocumentInput>,
+    ) -> Result<IndexResponse, McpError> {
+        let mut indexes = self.indexes.write().await;
+        let index = indexes
+            .get_mut(&name)
+            .ok_or_else(|| index_not_found(&name))?;
+
+        // update a copy, so that the index is left unchanged if it cannot be saved
+        let mut updated = index.clone();
+        let results = documents.into_iter().map(|d| updated.add(d)).collect();
+        self.save(&name, &updated).await?;
+        *index = updated;
+
+        Ok(IndexResponse {
+            results,
+            index_name: Some(name),
+            download_url: None,
+        })
+    }
+
+    pub async fn list_indexes(&self) -> Vec<KwIndexInfo> {
+        let indexes = self.indexes.read().await;
+
+        let mut infos: Vec<KwIndexInfo> = indexes
+            .iter()
+            .map(|(name, index)| KwIndexInfo {
+                name: name.clone(),
+                document_count: Some(index.documents.len() as u64),
+            })
+            .collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+
+        infos
+    }
+
+    /// Delete an index. Returns `false` if the index does not exist.
+    pub async fn delete_index(&self, name: &str) -> Result<bool, McpError> {
+        let mut indexes = self.indexes.write().await;
+        if !indexes.contains_key(name) {
+            return Ok(false);
+        }
+
+        match tokio::fs::remove_file(self.index_path(name)).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(storage_error(format!("Failed to delete index {name}: {e}")));
+            }
+        }
+        indexes.remove(name);
+
+        Ok(true)
+    }
+
+    /// Delete the documents with the given titles. Returns the number of deleted documents.
+    pub async fn delete_documents(&self, name: &str, titles: &[String]) -> Result<u64, McpError> {
+        let mut indexes = self.indexes.write().await;
+        let index = indexes.get_mut(name).ok_or_else(|| index_not_found(name))?;
+
+        let ids: Vec<u64> = index
+            .documents
+            .iter()
+            .filter(|(_, document)| {
+                document
+                    .title
+                    .as_ref()
+                    .is_some_and(|title| titles.contains(title))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        if !ids.is_empty() {
+            // update a copy, so that the index is left unchanged if it cannot be saved
+            let mut updated = index.clone();
+            for id in &ids {
+                updated.remove(*id);
+            }
+            self.save(name, &updated).await?;
+            *index = updated;
+        }
+
+        Ok(ids.len() as u64)
+    }
+
+    pub async fn search(
+        &self,
+        name: &str,
+        query: &str,
+        top_k: usize,
+    ) -> Result<QueryResponse, McpError> {
+        let indexes = self.indexes.read().await;
+        let index = indexes.get(name).ok_or_else(|| index_not_found(name))?;
+
+        Ok(QueryResponse {
+            hits: index.search(query, top_k),
+            error: None,
+        })
+    }
+
+    fn index_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.json"))
+    }
+
+    /// Write the index to a temporary file and move it into place, so that a crash never leaves a
+    /// truncated index behind.
+    async fn save(&self, name: &str, index: &InvertedIndex) -> Result<(), McpError> {
+        let bytes = serde_json::to_vec(index)
+            .map_err(|e| storage_error(format!("Failed to serialize index {name}: {e}")))?;
+
+        let path = self.index_path(name);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, bytes)
+            .await
+            .map_err(|e| storage_error(format!("Failed to save index {name}: {e}")))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| storage_error(format!("Failed to save index {name}: {e}")))
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct InvertedIndex {
+    next_id: u64,
+    documents: BTreeMap<u64, StoredDocument>,
+    /// term -> document id -> term frequency
+    postings: HashMap<String, BTreeMap<u64, u32>>,
+    /// sum of the lengths (in tokens) of all documents
+    total_length: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredDocument {
+    title: Option<String>,
+    content: String,
+    length: u32,
+}
+
+impl InvertedIndex {
+    fn add(&mut self, document: DocumentInput) -> DocumentResult {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        // the title is searchable as well as the content
+        let mut tokens = tokenize(&document.content);
+        if let Some(title) = &document.title {
+            tokens.extend(tokenize(title));
+        }
+
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *frequencies.entry(token.clone()).or_default() += 1;
+        }
+        for (term, frequency) in frequencies {
+            self.postings.entry(term).or_default().insert(id, frequency);
+        }
+
+        self.total_length += tokens.len() as u64;
+        self.documents.insert(
+            id,
+            StoredDocument {
+                title: document.title.clone(),
+                content: document.content,
+                length: tokens.len() as u32,
+            },
+        );
+
+        DocumentResult {
+            filename: document.title,
+            status: "indexed".to_string(),
+            error: None,
+        }
+    }
+
+    fn remove(&mut self, id: u64) {
+        let Some(document) = self.documents.remove(&id) else {
+            return;
+        };
+        self.total_length -= document.length as u64;
+
+        self.postings.retain(|_, documents| {
+            documents.remove(&id);
+            !documents.is_empty()
+        });
+    }
+
+    fn search(&self, query: &str, top_k: usize) -> Vec<SearchHit> {
+        if self.documents.is_empty() {
+            return vec![];
+        }
+
+        let document_count = self.documents.len() as f64;
+        let average_length = self.total_length as f64 / document_count;
+
+        let mut scores: HashMap<u64, f64> = HashMap::new();
+        for term in tokenize(query) {
+            let Some(documents) = self.postings.get(&term) else {
+                continue;
+            };
+
+            let df = documents.len() as f64;
+            let idf = (1.0 + (document_count - df + 0.5) / (df + 0.5)).ln();
+            for (id, frequency) in documents {
+                let tf = *frequency as f64;
+                let length = self.documents[id].length as f64;
+                let norm = K1 * (1.0 - B + B * length / average_length.max(1.0));
+                *scores.entry(*id).or_default() += idf * tf * (K1 + 1.0) / (tf + norm);
+            }
+        }
+
+        let mut scores: Vec<(u64, f64)> = scores.into_iter().collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scores.truncate(top_k);
+
+        scores
+            .into_iter()
+            .map(|(id, score)| {
+                let document = &self.documents[&id];
+                SearchHit {
+                    title: document.title.clone().unwrap_or_default(),
+                    content: document.content.clone(),
+                    score,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Split text into lowercase terms. Runs of CJK characters, which are not separated by spaces,
+/// are split into overlapping bigrams.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            cjk_run.push(c);
+        } else if c.is_alphanumeric() {
+            flush_cjk_run(&mut cjk_run, &mut tokens);
+            word.extend(c.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk_run(&mut cjk_run, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk_run(&mut cjk_run, &mut tokens);
+
+    tokens
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    if !word.is_empty() {
+        tokens.push(std::mem::take(word));
+    }
+}
+
+fn flush_cjk_run(run: &mut Vec<char>, tokens: &mut Vec<String>) {
+    match run.len() {
+        0 => {}
+        1 => tokens.push(run[0].to_string()),
+        _ => tokens.extend(run.windows(2).map(|pair| pair.iter().collect::<String>())),
+    }
+    run.clear();
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF // Hiragana, Katakana
+        | 0x3400..=0x4DBF // CJK Unified Ideographs Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF // Hangul Syllables
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+        | 0x20000..=0x2FA1F // CJK Unified Ideographs Extensions B-F, Compatibility Supplement
+    )
+}
+
+/// Index names become file names, so only allow characters that are safe in paths.
+fn validate_index_name(name: &str) -> Result<(), McpError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(invalid_params(format!(
+            "Invalid index name `{name}`. Only ASCII letters, digits, `-` and `_` are allowed"
+        ))),
+    }
+}
+
+fn index_not_found(name: &str) -> McpError {
+    invalid_params(format!("Index `{name}` not found"))
+}
+
+fn invalid_params(error_message: String) -> McpError {
+    error!("{}", error_message);
+    McpError::new(ErrorCode::INVALID_PARAMS, error_message, None)
+}
+
+fn storage_error(error_message: String) -> McpError {
+    error!("{}", error_message);
+    McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(title: &str, content: &str) -> DocumentInput {
+        DocumentInput {
+            content: content.to_string(),
+            title: Some(title.to_string()),
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cardea-kwsearch-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn tokenize_mixed_text() {
+        assert_eq!(
+            tokenize("Rust 语言教程, version 2!"),
+            vec!["rust", "语言", "言教", "教程", "version", "2"]
+        );
+        assert_eq!(tokenize("猫 cat"), vec!["猫", "cat"]);
+    }
+
+    #[test]
+    fn search_ranks_by_bm25() {
+        let mut index = InvertedIndex::default();
+        index.add(document("a", "the cat sat on the mat"));
+        index.add(document("b", "cat cat cat"));
+        index.add(document("c", "人工智能对教育的影响"));
+
+        let hits = index.search("cat", 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].title, "b");
+        assert!(hits[0].score > hits[1].score);
+
+        let hits = index.search("人工智能", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "c");
+
+        index.remove(1);
+        assert_eq!(index.search("cat", 10).len(), 1);
+        assert_eq!(index.postings["cat"].len(), 1);
+    }
+
+    #[tokio::test]
+    async fn store_persists_indexes() {
+        let dir = temp_dir("persist");
+
+        let store = EmbeddedStore::open(&dir).await.unwrap();
+        store
+            .create_index("docs".to_string(), vec![document("a", "hello world")])
+            .await
+            .unwrap();
+        store
+            .add_documents("docs".to_string(), vec![document("b", "hello rust")])
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .delete_documents("docs", &["a".to_string()])
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(
+            store
+                .create_index("../x".to_string(), vec![])
+                .await
+                .is_err()
+        );
+
+        let store = EmbeddedStore::open(&dir).await.unwrap();
+        let hits = store.search("docs", "hello", 10).await.unwrap().hits;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "b");
+        assert!(store.delete_index("docs").await.unwrap());
+        assert!(store.list_indexes().await.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_save_leaves_index_unchanged() {
+        let dir = temp_dir("failed-save");
+
+        let store = EmbeddedStore::open(&dir).await.unwrap();
+        store
+            .create_index("docs".to_string(), vec![document("a", "hello world")])
+            .await
+            .unwrap();
+
+        // the index can no longer be written
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            store
+                .add_documents("docs".to_string(), vec![document("b", "hello rust")])
+                .await
+                .is_err()
+        );
+        assert!(
+            store
+                .delete_documents("docs", &["a".to_string()])
+                .await
+                .is_err()
+        );
+
+        let hits = store.search("docs", "hello", 10).await.unwrap().hits;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "a");
+    }
+}
@@ -0,0 +1,414 @@
+use cardea_kwsearch_mcp_common::{ChunkingOptions, KwDocumentInput};
+use endpoints::keyword_search::{DocumentInput, DocumentResult};
+use rmcp::{ErrorData as McpError, model::ErrorCode};
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
+use walkdir::WalkDir;
+
+/// Extensions of the files that can be ingested
+const SUPPORTED_EXTENSIONS: &[&str] = &["txt", "md", "html", "htm", "pdf", "jsonl"];
+
+/// Width used to render HTML as text. Lines are only wrapped for display, so keep it wide.
+const HTML_TEXT_WIDTH: usize = 1000;
+
+/// Limits of the files ingested by a single call, so that a call cannot make the server read an
+/// unbounded amount of data.
+#[derive(Debug, Clone, Copy)]
+pub struct IngestLimits {
+    /// maximum number of files
+    pub max_files: usize,
+    /// maximum size of a file in bytes. Larger files are skipped and reported as failed.
+    pub max_file_size: u64,
+}
+
+/// Documents loaded from files, and the files that could not be loaded.
+#[derive(Debug, Default)]
+pub struct Ingested {
+    pub documents: Vec<DocumentInput>,
+    pub failures: Vec<DocumentResult>,
+}
+
+/// Load the documents of the given files and directories. Paths are relative to `root` and may not
+/// point outside of it. Files inside directories with unsupported extensions are skipped.
+pub fn load_documents(
+    root: &Path,
+    paths: &[String],
+    limits: IngestLimits,
+) -> Result<Ingested, McpError> {
+    let root = root.canonicalize().map_err(|e| {
+        internal_error(format!(
+            "Failed to resolve the ingestion root {}: {e}",
+            root.display()
+        ))
+    })?;
+
+    let too_many_files = || {
+        invalid_params(format!(
+            "The paths contain more than {} files, ingest them in several calls",
+            limits.max_files
+        ))
+    };
+
+    let mut files = Vec::new();
+    for path in paths {
+        let resolved = root
+            .join(path)
+            .canonicalize()
+            .map_err(|e| invalid_params(format!("Failed to resolve the path `{path}`: {e}")))?;
+        if !resolved.starts_with(&root) {
+            return Err(invalid_params(format!(
+                "The path `{path}` is outside of the ingestion root"
+            )));
+        }
+
+        if resolved.is_dir() {
+            let mut dir_files: Vec<PathBuf> = Vec::new();
+            let walk = WalkDir::new(&resolved)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+                .filter(|file| is_supported(file));
+            for file in walk {
+                if files.len() + dir_files.len() == limits.max_files {
+                    return Err(too_many_files());
+                }
+                dir_files.push(file);
+            }
+            dir_files.sort();
+
+            files.extend(dir_files);
+        } else if is_supported(&resolved) {
+            if files.len() == limits.max_files {
+                return Err(too_many_files());
+            }
+            files.push(resolved);
+        } else {
+            return Err(invalid_params(format!(
+                "Unsupported file type: `{path}`. Supported extensions: {}",
+                SUPPORTED_EXTENSIONS.join(", ")
+            )));
+        }
+    }
+
+    let mut ingested = Ingested::default();
+    for file in files {
+        load_file(&root, &file, limits.max_file_size, &mut ingested);
+    }
+
+    info!(
+        "Ingested {} documents, {} files failed",
+        ingested.documents.len(),
+        ingested.failures.len()
+    );
+
+    Ok(ingested)
+}
+
+fn is_supported(path: &Path) -> bool {
+    extension(path).is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+/// Load the documents of a file. The documents are titled with the path of the file relative to
+/// the root, so that all the documents of a file can be deleted by that title. Files larger than
+/// `max_file_size` bytes are not read.
+fn load_file(root: &Path, file: &Path, max_file_size: u64, ingested: &mut Ingested) {
+    let title = file
+        .strip_prefix(root)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .into_owned();
+
+    let result = match std::fs::metadata(file) {
+        Ok(metadata) if metadata.len() > max_file_size => Err(format!(
+            "The file is larger than the maximum of {max_file_size} bytes"
+        )),
+        Ok(_) => read_file(file, &title),
+        Err(e) => Err(e.to_string()),
+    };
+
+    match result {
+        Ok(documents) => ingested.documents.extend(documents),
+        Err(e) => {
+            warn!("Failed to ingest {}: {}", file.display(), e);
+            ingested.failures.push(DocumentResult {
+                filename: Some(title),
+                status: "failed".to_string(),
+                error: Some(e),
+            });
+        }
+    }
+}
+
+/// Read the documents of a file according to its extension.
+fn read_file(file: &Path, title: &str) -> Result<Vec<DocumentInput>, String> {
+    match extension(file).as_deref() {
+        Some("jsonl") => read_jsonl(file, title),
+        Some("pdf") => std::fs::read(file)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| extract_pdf_text(&bytes))
+            .map(|content| vec![document(content, title)]),
+        Some("html") | Some("htm") => std::fs::read(file)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| {
+                html2text::config::plain_no_decorate()
+                    .string_from_read(bytes.as_slice(), HTML_TEXT_WIDTH)
+                    .map_err(|e| e.to_string())
+            })
+            .map(|content| vec![document(content, title)]),
+        _ => std::fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .map(|content| vec![document(content, title)]),
+    }
+}
+
+/// Extract the text of a PDF. `pdf_extract` panics on some malformed or unsupported files, so a
+/// panic is caught and reported as a failure of the file instead of aborting the whole ingestion.
+fn extract_pdf_text(bytes: &[u8]) -> Result<String, String> {
+    match std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes)) {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(panic) => {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .map(|reason| reason.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown error".to_string());
+            Err(format!("Failed to extract the text of the PDF: {reason}"))
+        }
+    }
+}
+
+fn document(content: String, title: &str) -> DocumentInput {
+    DocumentInput {
+        content,
+        title: Some(title.to_string()),
+    }
+}
+
+/// Read one document per line. Documents without a title are titled with the path of the file.
+fn read_jsonl(file: &Path, title: &str) -> Result<Vec<DocumentInput>, String> {
+    let text = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let input: KwDocumentInput = serde_json::from_str(line)
+                .map_err(|e| format!("Invalid document on line {}: {e}", number + 1))?;
+            Ok(DocumentInput {
+                content: input.content,
+                title: Some(input.title.unwrap_or_else(|| title.to_string())),
+            })
+        })
+        .collect()
+}
+
+/// Check that the chunking options are usable.
+pub fn validate_chunking(chunking: &ChunkingOptions) -> Result<(), McpError> {
+    if chunking.chunk_size == 0 || chunking.chunk_overlap >= chunking.chunk_size {
+        return Err(invalid_params(
+            "`chunk_size` must be greater than 0 and greater than `chunk_overlap`".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Split documents into chunks of at most `chunk_size` characters. Chunks end at whitespace where
+/// possible, and consecutive chunks share `chunk_overlap` characters. Chunks keep the title of
+/// their document.
+pub fn chunk_documents(
+    documents: Vec<DocumentInput>,
+    chunking: &ChunkingOptions,
+) -> Vec<DocumentInput> {
+    documents
+        .into_iter()
+        .flat_map(|document| {
+            let title = document.title;
+            split_text(&document.content, chunking)
+                .into_iter()
+                .map(move |content| DocumentInput {
+                    content,
+                    title: title.clone(),
+                })
+        })
+        .collect()
+}
+
+fn split_text(text: &str, chunking: &ChunkingOptions) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + chunking.chunk_size).min(chars.len());
+        if end < chars.len() {
+            // break at the last whitespace of the second half of the chunk, if any
+            let min_end = start + chunking.chunk_size / 2;
+            if let Some(space) = (min_end.max(start + 1)..end)
+                .rev()
+                .find(|&i| chars[i].is_whitespace())
+            {
+                end = space;
+            }
+        }
+
+        let chunk: String = chars[start..end].iter().collect();
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk.trim().to_string());
+        }
+        if end == chars.len() {
+            break;
+        }
+
+        // start the next chunk at the beginning of a word, if one is close enough
+        let mut next = end.saturating_sub(chunking.chunk_overlap);
+        if next > 0 && !chars[next - 1].is_whitespace() {
+            let floor = next.saturating_sub(chunking.chunk_overlap).max(start + 1);
+            if let Some(word_start) = (floor..next).rev().find(|&i| chars[i - 1].is_whitespace()) {
+                next = word_start;
+            }
+        }
+        start = next.max(start + 1);
+    }
+
+    chunks
+}
+
+fn invalid_params(error_message: String) -> McpError {
+    error!("{}", error_message);
+    McpError::new(ErrorCode::INVALID_PARAMS, error_message, None)
+}
+
+fn internal_error(error_message: String) -> McpError {
+    error!("{}", error_message);
+    McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: IngestLimits = IngestLimits {
+        max_files: 100,
+        max_file_size: 1024,
+    };
+
+    #[test]
+    fn split_text_at_whitespace_with_overlap() {
+        let chunking = ChunkingOptions {
+            chunk_size: 12,
+            chunk_overlap: 4,
+        };
+
+        assert_eq!(
+            split_text("alpha beta gamma delta", &chunking),
+            vec!["alpha beta", "beta gamma", "gamma delta"]
+        );
+        assert_eq!(split_text("短文本", &chunking), vec!["短文本"]);
+    }
+
+    #[test]
+    fn load_documents_from_root() {
+        let root = std::env::temp_dir().join(format!("cardea-ingest-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/a.md"), "# Title\n\nSome text").unwrap();
+        std::fs::write(root.join("docs/b.html"), "<p>Hello <b>world</b></p>").unwrap();
+        std::fs::write(
+            root.join("docs/c.jsonl"),
+            "{\"content\": \"first\", \"title\": \"one\"}\n\n{\"content\": \"second\"}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("docs/d.bin"), [0u8, 1, 2]).unwrap();
+
+        let ingested = load_documents(&root, &["docs".to_string()], LIMITS).unwrap();
+        let titles: Vec<_> = ingested
+            .documents
+            .iter()
+            .map(|d| d.title.clone().unwrap())
+            .collect();
+        assert_eq!(
+            titles,
+            vec!["docs/a.md", "docs/b.html", "one", "docs/c.jsonl"]
+        );
+        assert_eq!(ingested.documents[1].content.trim(), "Hello world");
+        assert!(ingested.failures.is_empty());
+
+        assert!(load_documents(&root, &["..".to_string()], LIMITS).is_err());
+        assert!(load_documents(&root, &["docs/d.bin".to_string()], LIMITS).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn ingestion_is_limited() {
+        let root =
+            std::env::temp_dir().join(format!("cardea-ingest-limits-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/a.txt"), "small").unwrap();
+        std::fs::write(root.join("docs/b.txt"), "x".repeat(2048)).unwrap();
+        std::fs::write(root.join("c.txt"), "small").unwrap();
+
+        // files over the maximum size are skipped and reported
+        let ingested = load_documents(&root, &["docs".to_string()], LIMITS).unwrap();
+        assert_eq!(ingested.documents.len(), 1);
+        assert_eq!(ingested.failures.len(), 1);
+        assert_eq!(ingested.failures[0].filename.as_deref(), Some("docs/b.txt"));
+
+        // more files than the maximum are rejected
+        let limits = IngestLimits {
+            max_files: 2,
+            ..LIMITS
+        };
+        assert!(load_documents(&root, &["docs".to_string()], limits).is_ok());
+        let error =
+            load_documents(&root, &["docs".to_string(), "c.txt".to_string()], limits).unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert!(load_documents(&root, &[".".to_string()], limits).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn malformed_pdf_is_a_failed_file() {
+        // a page without a MediaBox makes pdf_extract panic
+        let pdf = concat!(
+            "%PDF-1.4\n",
+            "1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n",
+            "2 0 obj\n<< /Type /Pages /Kids [3 0 R] /Count 1 >>\nendobj\n",
+            "3 0 obj\n<< /Type /Page /Parent 2 0 R >>\nendobj\n",
+            "xref\n0 4\n",
+            "0000000000 65535 f \n",
+            "0000000009 00000 n \n",
+            "0000000058 00000 n \n",
+            "0000000115 00000 n \n",
+            "trailer\n<< /Size 4 /Root 1 0 R >>\nstartxref\n162\n%%EOF\n",
+        );
+        let root = std::env::temp_dir().join(format!("cardea-ingest-pdf-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("broken.pdf"), pdf).unwrap();
+        std::fs::write(root.join("ok.txt"), "Some text").unwrap();
+
+        let ingested = load_documents(&root, &[".".to_string()], LIMITS).unwrap();
+        assert_eq!(ingested.documents.len(), 1);
+        assert_eq!(ingested.failures.len(), 1);
+        assert_eq!(ingested.failures[0].filename.as_deref(), Some("broken.pdf"));
+        assert!(
+            ingested.failures[0]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("MediaBox")
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,12 +1,17 @@
+mod embedded;
+mod ingest;
 mod search;
 
 use clap::{Parser, ValueEnum};
+use embedded::EmbeddedStore;
+use ingest::IngestLimits;
 use once_cell::sync::OnceCell;
 use rmcp::transport::{
     sse_server::SseServer,
     streamable_http_server::{StreamableHttpService, session::local::LocalSessionManager},
 };
 use search::{ConnectionConfig, KeywordSearchServer};
+use std::{path::PathBuf, sync::Arc};
 use tokio::sync::RwLock as TokioRwLock;
 use tracing_subscriber::{self, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -20,18 +25,47 @@ struct Args {
     /// The base URL of the kw-search-server
     #[arg(long, default_value = "http://127.0.0.1:12306")]
     base_url: String,
+    /// API key sent as a bearer token to the kw-search-server. Falls back to the
+    /// `KW_SEARCH_API_KEY` environment variable.
+    #[arg(long)]
+    api_key: Option<String>,
+    /// Path to a PEM-encoded CA certificate bundle used to verify the kw-search-server
+    #[arg(long)]
+    ca_cert: Option<PathBuf>,
+    /// Path to a PEM-encoded client certificate used for mutual TLS
+    #[arg(long, requires = "client_key")]
+    client_cert: Option<PathBuf>,
+    /// Path to the PEM-encoded private key of the client certificate
+    #[arg(long, requires = "client_cert")]
+    client_key: Option<PathBuf>,
+    /// Directory of the embedded keyword search backend. If set, the indexes are kept in this
+    /// directory instead of a kw-search-server
+    #[arg(long)]
+    embedded_dir: Option<PathBuf>,
+    /// Directory from which the index tools may ingest files. File ingestion is disabled if not set
+    #[arg(long)]
+    ingest_root: Option<PathBuf>,
+    /// Maximum number of files a single call may ingest
+    #[arg(long, default_value = "1000")]
+    max_ingest_files: usize,
+    /// Maximum size in bytes of an ingested file. Larger files are skipped and reported as failed
+    #[arg(long, default_value = "10485760")]
+    max_ingest_file_size: u64,
     /// Socket address to bind to
     #[arg(short, long, default_value = DEFAULT_SOCKET_ADDR)]
     socket_addr: String,
     /// Transport type to use
     #[arg(short, long, value_enum, default_value = "stream-http")]
     transport: TransportType,
-    /// Index to search
+    /// Index to search when a search does not specify one
     #[arg(long)]
-    index: String,
-    /// Maximum number of query results to return
+    index: Option<String>,
+    /// Maximum number of query results to return when a search does not specify `top_k`
     #[arg(long, default_value = "10")]
     limit: usize,
+    /// Upper bound for the `top_k` of a search
+    #[arg(long, default_value = "100")]
+    max_top_k: usize,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -52,11 +86,47 @@ async fn main() -> anyhow::Result<()> {
 
     let args = Args::parse();
 
+    if args.limit == 0 || args.limit > args.max_top_k {
+        anyhow::bail!(
+            "--limit must be between 1 and --max-top-k ({})",
+            args.max_top_k
+        );
+    }
+
+    let embedded = match args.embedded_dir {
+        Some(dir) => {
+            tracing::info!(
+                "Using the embedded keyword search backend in {}",
+                dir.display()
+            );
+            Some(Arc::new(EmbeddedStore::open(dir).await?))
+        }
+        None => None,
+    };
+
+    // build http client
+    let client = cardea_common::build_http_client(
+        args.ca_cert.as_deref(),
+        args.client_cert.as_deref(),
+        args.client_key.as_deref(),
+    )?;
+
+    let embedded_tools = embedded.is_some();
     let connection_config = ConnectionConfig {
         base_url: args.base_url,
-        api_key: None,
+        api_key: args
+            .api_key
+            .or_else(|| std::env::var("KW_SEARCH_API_KEY").ok()),
+        client,
+        embedded,
+        ingest_root: args.ingest_root,
+        ingest_limits: IngestLimits {
+            max_files: args.max_ingest_files,
+            max_file_size: args.max_ingest_file_size,
+        },
         index: args.index,
         limit: args.limit,
+        max_top_k: args.max_top_k,
     };
 
     CONNECTION_CONFIG
@@ -71,7 +141,7 @@ async fn main() -> anyhow::Result<()> {
     match args.transport {
         TransportType::StreamHttp => {
             let service = StreamableHttpService::new(
-                || Ok(KeywordSearchServer::new()),
+                move || Ok(KeywordSearchServer::new(embedded_tools)),
                 LocalSessionManager::default().into(),
                 Default::default(),
             );
@@ -85,7 +155,7 @@ async fn main() -> anyhow::Result<()> {
         TransportType::Sse => {
             let ct = SseServer::serve(args.socket_addr.parse()?)
                 .await?
-                .with_service(KeywordSearchServer::new);
+                .with_service(move || KeywordSearchServer::new(embedded_tools));
 
             tokio::signal::ctrl_c().await?;
             ct.cancel();
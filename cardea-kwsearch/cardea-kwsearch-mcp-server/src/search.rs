@@ -1,8 +1,16 @@
-use crate::CONNECTION_CONFIG;
+use crate::{
+    CONNECTION_CONFIG,
+    embedded::EmbeddedStore,
+    ingest::{self, IngestLimits},
+};
 use cardea_kwsearch_mcp_common::{
-    CreateIndexRequest, CreateIndexResponse, SearchDocumentsRequest, SearchDocumentsResponse,
+    AddDocumentsRequest, AddDocumentsResponse, BACKEND_UNAVAILABLE, ChunkingOptions,
+    CreateIndexRequest, CreateIndexResponse, DeleteDocumentsRequest, DeleteDocumentsResponse,
+    DeleteIndexRequest, DeleteIndexResponse, KwDocumentInput, ListIndexesResponse,
+    SearchDocumentsRequest, SearchDocumentsResponse,
 };
-use endpoints::keyword_search::{IndexRequest, IndexResponse, QueryRequest, QueryResponse};
+use endpoints::keyword_search::{DocumentInput, DocumentResult, IndexRequest, QueryRequest};
+use reqwest::{Method, StatusCode};
 use rmcp::{
     ErrorData as McpError, RoleServer, ServerHandler,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
@@ -10,7 +18,10 @@ use rmcp::{
     service::RequestContext,
     tool, tool_handler, tool_router,
 };
-use tracing::{error, info};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use std::{path::PathBuf, sync::Arc};
+use tracing::{error, info, warn};
 
 const PROMPT_SEARCH_TOOL: &str = r#"
 You are a multilingual AI assistant. Your task is to (1) extract the most relevant and concise keywords or key phrases from the given user query, and (2) return a tool call that invokes the `search` tool with the extracted keywords.
@@ -48,22 +59,41 @@ Examples:
     </tool_call>
 "#;
 
+/// Tools that manage the indexes of the embedded backend. kw-search-server only provides
+/// `create_index` and `search`, so these are not registered when proxying it.
+const EMBEDDED_TOOLS: [&str; 4] = [
+    "list_indexes",
+    "delete_index",
+    "add_documents",
+    "delete_documents",
+];
+
 #[derive(Debug, Clone)]
 pub struct KeywordSearchServer {
     tool_router: ToolRouter<Self>,
 }
 #[tool_router]
 impl KeywordSearchServer {
-    pub fn new() -> Self {
-        Self {
-            tool_router: Self::tool_router(),
+    pub fn new(embedded: bool) -> Self {
+        let mut tool_router = Self::tool_router();
+        if !embedded {
+            for name in EMBEDDED_TOOLS {
+                tool_router.remove_route(name);
+            }
         }
+
+        Self { tool_router }
     }
 
     #[tool(description = "Create an index in the KeywordSearch database")]
     async fn create_index(
         &self,
-        Parameters(CreateIndexRequest { index, documents }): Parameters<CreateIndexRequest>,
+        Parameters(CreateIndexRequest {
+            index,
+            documents,
+            paths,
+            chunking,
+        }): Parameters<CreateIndexRequest>,
     ) -> Result<CallToolResult, McpError> {
         info!("Creating index in KeywordSearch database");
 
@@ -84,49 +114,172 @@ impl KeywordSearchServer {
             }
         };
 
-        // build url
-        let base_url = conn_config.base_url.trim_end_matches('/');
-        let url = format!("{base_url}/v1/index/create");
+        let (documents, failures) =
+            prepare_documents(&conn_config, documents, paths, chunking).await?;
 
-        let index_request = IndexRequest {
-            index: Some(index),
-            documents: documents.into_iter().map(|d| d.into()).collect::<Vec<_>>(),
+        let mut index_response = match &conn_config.embedded {
+            Some(store) => store.create_index(index, documents).await?,
+            None => {
+                let index_request = IndexRequest {
+                    index: Some(index),
+                    documents,
+                };
+
+                send_request(
+                    &conn_config,
+                    Method::POST,
+                    "/v1/index/create",
+                    Some(&index_request),
+                    "create index",
+                )
+                .await?
+            }
         };
 
-        let response = reqwest::Client::new()
-            .post(&url)
-            .json(&index_request)
-            .send()
-            .await
-            .map_err(|e| {
-                let error_message = format!("Failed to create index: {e}");
+        // report the files that could not be ingested
+        index_response.results.extend(failures);
 
+        let content = Content::json(CreateIndexResponse::from(index_response))?;
+
+        info!("Index created in KeywordSearch database");
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "Perform a keyword search")]
+    async fn search(
+        &self,
+        Parameters(SearchDocumentsRequest {
+            query,
+            index,
+            top_k,
+        }): Parameters<SearchDocumentsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        info!("Searching for documents in KeywordSearch database");
+
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
                 error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let index = match index.or_else(|| conn_config.index.clone()) {
+            Some(index) => index,
+            None => {
+                let error_message = "No index specified and no default index configured";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+        };
 
-                McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-            })?;
+        let top_k = match top_k {
+            Some(0) => {
+                let error_message = "`top_k` must be greater than 0";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+            Some(top_k) if top_k > conn_config.max_top_k => {
+                warn!(
+                    "`top_k` ({}) exceeds the maximum ({}), capping it",
+                    top_k, conn_config.max_top_k
+                );
+                conn_config.max_top_k
+            }
+            Some(top_k) => top_k,
+            None => conn_config.limit,
+        };
 
-        let index_response = response.json::<IndexResponse>().await.map_err(|e| {
-            let error_message = format!("Failed to parse index response: {e}");
+        let query_response = match &conn_config.embedded {
+            Some(store) => store.search(&index, &query, top_k).await?,
+            None => {
+                let query_request = QueryRequest {
+                    query,
+                    top_k,
+                    index,
+                };
+
+                send_request(
+                    &conn_config,
+                    Method::POST,
+                    "/v1/search",
+                    Some(&query_request),
+                    "search documents",
+                )
+                .await?
+            }
+        };
 
+        if let Some(e) = query_response.error {
+            let error_message = format!("Failed to search documents: {e}");
             error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                error_message,
+                None,
+            ));
+        }
 
-            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-        })?;
+        let content = Content::json(SearchDocumentsResponse::from(query_response))?;
 
-        let content = Content::json(CreateIndexResponse::from(index_response))?;
+        info!("Documents searched in KeywordSearch database");
 
-        info!("Index created in KeywordSearch database");
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "List the indexes in the KeywordSearch database")]
+    async fn list_indexes(&self) -> Result<CallToolResult, McpError> {
+        info!("Listing indexes in KeywordSearch database");
+
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+        let indexes = embedded_store(&conn_config)?.list_indexes().await;
+
+        let content = Content::json(ListIndexesResponse { indexes })?;
+
+        info!("Indexes listed in KeywordSearch database");
 
         Ok(CallToolResult::success(vec![content]))
     }
 
-    #[tool(description = "Perform a keyword search")]
-    async fn search(
+    #[tool(description = "Delete an index from the KeywordSearch database")]
+    async fn delete_index(
         &self,
-        Parameters(SearchDocumentsRequest { query }): Parameters<SearchDocumentsRequest>,
+        Parameters(DeleteIndexRequest { index }): Parameters<DeleteIndexRequest>,
     ) -> Result<CallToolResult, McpError> {
-        info!("Searching for documents in KeywordSearch database");
+        info!("Deleting index {} from KeywordSearch database", index);
 
         // get connection config
         let conn_config = match CONNECTION_CONFIG.get() {
@@ -144,41 +297,97 @@ impl KeywordSearchServer {
                 ));
             }
         };
+        let deleted = embedded_store(&conn_config)?.delete_index(&index).await?;
 
-        // build url
-        let base_url = conn_config.base_url.trim_end_matches('/');
-        let url = format!("{base_url}/v1/search");
+        let content = Content::json(DeleteIndexResponse { index, deleted })?;
 
-        let query_request = QueryRequest {
-            query,
-            top_k: conn_config.limit,
-            index: conn_config.index,
-        };
+        info!("Index deleted from KeywordSearch database");
 
-        let response = reqwest::Client::new()
-            .post(&url)
-            .json(&query_request)
-            .send()
-            .await
-            .map_err(|e| {
-                let error_message = format!("Failed to search documents: {e}");
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "Add documents to an existing index in the KeywordSearch database")]
+    async fn add_documents(
+        &self,
+        Parameters(AddDocumentsRequest {
+            index,
+            documents,
+            paths,
+            chunking,
+        }): Parameters<AddDocumentsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        info!(
+            "Adding documents to index {} in KeywordSearch database",
+            index
+        );
 
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
                 error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+        let store = embedded_store(&conn_config)?;
+        let (documents, failures) =
+            prepare_documents(&conn_config, documents, paths, chunking).await?;
 
-                McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-            })?;
+        let mut index_response = store.add_documents(index, documents).await?;
 
-        let query_response = response.json::<QueryResponse>().await.map_err(|e| {
-            let error_message = format!("Failed to parse query response: {e}");
+        // report the files that could not be ingested
+        index_response.results.extend(failures);
 
-            error!("{}", error_message);
+        let content = Content::json(AddDocumentsResponse::from(index_response))?;
 
-            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-        })?;
+        info!("Documents added to KeywordSearch database");
 
-        let content = Content::json(SearchDocumentsResponse::from(query_response))?;
+        Ok(CallToolResult::success(vec![content]))
+    }
 
-        info!("Documents searched in KeywordSearch database");
+    #[tool(
+        description = "Delete documents, identified by their titles, from an index in the KeywordSearch database"
+    )]
+    async fn delete_documents(
+        &self,
+        Parameters(DeleteDocumentsRequest { index, titles }): Parameters<DeleteDocumentsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        info!(
+            "Deleting documents from index {} in KeywordSearch database",
+            index
+        );
+
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+        let deleted = embedded_store(&conn_config)?
+            .delete_documents(&index, &titles)
+            .await?;
+
+        let content = Content::json(DeleteDocumentsResponse { index, deleted })?;
+
+        info!("Documents deleted from KeywordSearch database");
 
         Ok(CallToolResult::success(vec![content]))
     }
@@ -250,11 +459,357 @@ impl ServerHandler for KeywordSearchServer {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ConnectionConfig {
     pub base_url: String,
-    #[allow(dead_code)]
+    /// api key sent as a bearer token on every request to the kw-search-server
     pub api_key: Option<String>,
-    pub index: String,
+    /// http client configured with the TLS options
+    pub client: reqwest::Client,
+    /// embedded backend, used instead of the kw-search-server when set
+    pub embedded: Option<Arc<EmbeddedStore>>,
+    /// directory under which files may be ingested
+    pub ingest_root: Option<PathBuf>,
+    /// limits of the files ingested by a call
+    pub ingest_limits: IngestLimits,
+    /// default index to search
+    pub index: Option<String>,
+    /// default number of search results
     pub limit: usize,
+    /// upper bound for the number of search results
+    pub max_top_k: usize,
+}
+
+impl std::fmt::Debug for ConnectionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never print the api key
+        f.debug_struct("ConnectionConfig")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .field("client", &self.client)
+            .field("embedded", &self.embedded)
+            .field("ingest_root", &self.ingest_root)
+            .field("ingest_limits", &self.ingest_limits)
+            .field("index", &self.index)
+            .field("limit", &self.limit)
+            .field("max_top_k", &self.max_top_k)
+            .finish()
+    }
+}
+
+/// The embedded backend, which the index management tools require.
+fn embedded_store(conn_config: &ConnectionConfig) -> Result<Arc<EmbeddedStore>, McpError> {
+    conn_config.embedded.clone().ok_or_else(|| {
+        let error_message =
+            "This tool requires the embedded backend. Start the server with `--embedded-dir`";
+        error!("{}", error_message);
+        McpError::new(ErrorCode::INVALID_REQUEST, error_message, None)
+    })
+}
+
+/// Collect the inline documents and the documents ingested from files, split into chunks if
+/// requested. Also returns the results of the files that could not be ingested.
+async fn prepare_documents(
+    conn_config: &ConnectionConfig,
+    documents: Vec<KwDocumentInput>,
+    paths: Vec<String>,
+    chunking: Option<ChunkingOptions>,
+) -> Result<(Vec<DocumentInput>, Vec<DocumentResult>), McpError> {
+    if let Some(chunking) = &chunking {
+        ingest::validate_chunking(chunking)?;
+    }
+
+    let mut documents: Vec<DocumentInput> = documents.into_iter().map(|d| d.into()).collect();
+
+    let mut failures = Vec::new();
+    if !paths.is_empty() {
+        let root = match &conn_config.ingest_root {
+            Some(root) => root.clone(),
+            None => {
+                let error_message = "File ingestion is disabled. Start the server with `--ingest-root` to enable it";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        // reading and parsing files is blocking
+        let limits = conn_config.ingest_limits;
+        let ingested =
+            tokio::task::spawn_blocking(move || ingest::load_documents(&root, &paths, limits))
+                .await
+                .map_err(|e| {
+                    let error_message = format!("Failed to ingest files: {e}");
+                    error!("{}", error_message);
+                    McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+                })??;
+        documents.extend(ingested.documents);
+        failures = ingested.failures;
+    }
+
+    let documents = match &chunking {
+        Some(chunking) => ingest::chunk_documents(documents, chunking),
+        None => documents,
+    };
+
+    Ok((documents, failures))
+}
+
+/// Send a request to the given path of the kw-search-server and parse the response.
+async fn send_request<T: DeserializeOwned>(
+    conn_config: &ConnectionConfig,
+    method: Method,
+    path: &str,
+    body: Option<&impl Serialize>,
+    action: &str,
+) -> Result<T, McpError> {
+    // build url
+    let base_url = conn_config.base_url.trim_end_matches('/');
+    let url = format!("{base_url}{path}");
+
+    let mut request = conn_config.client.request(method, &url);
+    if let Some(api_key) = &conn_config.api_key {
+        request = request.bearer_auth(api_key);
+    }
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        let error_message = format!("Failed to {action}: {e}");
+
+        error!("{}", error_message);
+
+        McpError::new(BACKEND_UNAVAILABLE, error_message, None)
+    })?;
+
+    let status = response.status();
+    let text = response.text().await.map_err(|e| {
+        let error_message = format!("Failed to {action}: {e}");
+
+        error!("{}", error_message);
+
+        McpError::new(BACKEND_UNAVAILABLE, error_message, None)
+    })?;
+
+    if !status.is_success() {
+        // return the error reported by the kw-search-server, as JSON if possible
+        let body = serde_json::from_str::<Value>(&text).unwrap_or(Value::String(text));
+        let detail = match body.get("error").and_then(|error| error.as_str()) {
+            Some(error) => error.to_string(),
+            None => match &body {
+                Value::String(text) if !text.is_empty() => text.clone(),
+                _ => status.to_string(),
+            },
+        };
+        let error_message = format!("Failed to {action}: {detail} (HTTP {})", status.as_u16());
+
+        error!("{}", error_message);
+
+        let code = error_code(status);
+        return Err(McpError::new(
+            code,
+            error_message,
+            Some(json!({ "status": status.as_u16(), "body": body })),
+        ));
+    }
+
+    serde_json::from_str::<T>(&text).map_err(|e| {
+        let error_message = format!("Failed to parse the response to {action}: {e}");
+
+        error!("{}", error_message);
+
+        McpError::new(
+            ErrorCode::INTERNAL_ERROR,
+            error_message,
+            Some(json!({ "body": text })),
+        )
+    })
+}
+
+/// Map an HTTP error status of the kw-search-server to an MCP error code. Only the 4xx statuses
+/// caused by the arguments of the call are reported as invalid params.
+fn error_code(status: StatusCode) -> ErrorCode {
+    match status {
+        // the credentials of the mcp server are missing or rejected
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorCode::INTERNAL_ERROR,
+        // rate limited: the call may succeed later
+        StatusCode::TOO_MANY_REQUESTS => BACKEND_UNAVAILABLE,
+        status if status.is_client_error() => ErrorCode::INVALID_PARAMS,
+        _ => BACKEND_UNAVAILABLE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use endpoints::keyword_search::QueryResponse;
+
+    fn conn_config(base_url: String) -> ConnectionConfig {
+        ConnectionConfig {
+            base_url,
+            api_key: None,
+            client: reqwest::Client::new(),
+            embedded: None,
+            ingest_root: None,
+            ingest_limits: IngestLimits {
+                max_files: 1000,
+                max_file_size: 10 * 1024 * 1024,
+            },
+            index: None,
+            limit: 10,
+            max_top_k: 100,
+        }
+    }
+
+    /// Start a mock kw-search-server answering every request with the given status and body.
+    async fn mock_server(status: StatusCode, body: &'static str) -> ConnectionConfig {
+        let app = Router::new().fallback(move || async move { (status, body) });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        conn_config(format!("http://{addr}"))
+    }
+
+    async fn search(conn_config: &ConnectionConfig) -> Result<QueryResponse, McpError> {
+        let query_request = QueryRequest {
+            query: "hello".to_string(),
+            top_k: 5,
+            index: "test01".to_string(),
+        };
+
+        send_request(
+            conn_config,
+            Method::POST,
+            "/v1/search",
+            Some(&query_request),
+            "search documents",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn client_error_is_invalid_params() {
+        let conn_config = mock_server(
+            StatusCode::NOT_FOUND,
+            r#"{"error": "Index not found: test01"}"#,
+        )
+        .await;
+
+        let error = search(&conn_config).await.unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(
+            error.message,
+            "Failed to search documents: Index not found: test01 (HTTP 404)"
+        );
+        assert_eq!(
+            error.data.unwrap(),
+            json!({ "status": 404, "body": { "error": "Index not found: test01" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn server_error_is_backend_unavailable() {
+        let conn_config = mock_server(StatusCode::SERVICE_UNAVAILABLE, "overloaded").await;
+
+        let error = search(&conn_config).await.unwrap_err();
+
+        assert_eq!(error.code, BACKEND_UNAVAILABLE);
+        assert_eq!(error.data.unwrap()["body"], "overloaded");
+    }
+
+    #[tokio::test]
+    async fn auth_and_rate_limit_errors_are_not_invalid_params() {
+        for (status, code) in [
+            (StatusCode::UNAUTHORIZED, ErrorCode::INTERNAL_ERROR),
+            (StatusCode::FORBIDDEN, ErrorCode::INTERNAL_ERROR),
+            (StatusCode::TOO_MANY_REQUESTS, BACKEND_UNAVAILABLE),
+        ] {
+            let conn_config = mock_server(status, r#"{"error": "denied"}"#).await;
+
+            let error = search(&conn_config).await.unwrap_err();
+
+            assert_eq!(error.code, code, "{status}");
+            assert_eq!(error.data.unwrap()["status"], status.as_u16());
+        }
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_is_backend_unavailable() {
+        // bind and drop a listener to get a port nothing listens on
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let error = search(&conn_config(format!("http://{addr}")))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code, BACKEND_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn api_key_is_sent_as_bearer_token() {
+        // answer with the authorization header the request carried
+        let app = Router::new().fallback(|headers: axum::http::HeaderMap| async move {
+            let authorization = headers
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            format!(r#"{{"hits": [{{"title": "{authorization}", "content": "", "score": 0.0}}]}}"#)
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut conn_config = conn_config(format!("http://{addr}"));
+        conn_config.api_key = Some("secret".to_string());
+
+        let response = search(&conn_config).await.unwrap();
+
+        assert_eq!(response.hits[0].title, "Bearer secret");
+    }
+
+    #[test]
+    fn debug_hides_api_key() {
+        let mut conn_config = conn_config("http://127.0.0.1:12306".to_string());
+        conn_config.api_key = Some("secret".to_string());
+
+        let debug = format!("{conn_config:?}");
+        assert!(!debug.contains("secret"), "{debug}");
+        assert!(debug.contains("http://127.0.0.1:12306"));
+    }
+
+    #[test]
+    fn embedded_tools_require_embedded_backend() {
+        let names = |server: KeywordSearchServer| -> Vec<String> {
+            server
+                .tool_router
+                .list_all()
+                .into_iter()
+                .map(|tool| tool.name.to_string())
+                .collect()
+        };
+
+        let tools = names(KeywordSearchServer::new(false));
+        assert!(tools.contains(&"create_index".to_string()));
+        assert!(tools.contains(&"search".to_string()));
+        for name in EMBEDDED_TOOLS {
+            assert!(!tools.contains(&name.to_string()), "{name}");
+        }
+
+        let tools = names(KeywordSearchServer::new(true));
+        for name in EMBEDDED_TOOLS {
+            assert!(tools.contains(&name.to_string()), "{name}");
+        }
+    }
 }
@@ -103,8 +103,113 @@ impl From<CallToolResult> for UpsertPointsResponse {
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SearchPointsRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
     #[schemars(description = "The vector to search for")]
     pub vector: Vec<f32>,
+    #[schemars(
+        description = "The name of the vector to search, for collections with named vectors"
+    )]
+    #[serde(default)]
+    pub using: Option<String>,
+    #[schemars(description = "Whether to return the vectors of the points. Defaults to false")]
+    #[serde(default)]
+    pub with_vector: bool,
+    #[schemars(
+        description = "The payload fields to return. All payload fields are returned by default"
+    )]
+    #[serde(default)]
+    pub payload_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct QueryPointsRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(
+        description = "The dense or sparse vector to search for. Either `query` or `fusion` must be provided"
+    )]
+    #[serde(default)]
+    pub query: Option<QueryVector>,
+    #[schemars(
+        description = "The name of the vector to search, for collections with named vectors. Not allowed with `fusion`, set it on the prefetch queries instead"
+    )]
+    #[serde(default)]
+    pub using: Option<String>,
+    #[schemars(
+        description = "Sub-queries whose results are used as candidates for the main query, or fused when `fusion` is provided"
+    )]
+    #[serde(default)]
+    pub prefetch: Vec<Prefetch>,
+    #[schemars(description = "The method used to fuse the results of the prefetch queries")]
+    #[serde(default)]
+    pub fusion: Option<Fusion>,
+    #[schemars(description = "Filter conditions in the Qdrant filter format")]
+    #[serde(default)]
+    pub filter: Option<Value>,
+    #[schemars(
+        description = "Maximum number of results to return, greater than 0. Defaults to the limit configured on the server, and is capped at the maximum configured on the server"
+    )]
+    #[serde(default)]
+    pub limit: Option<u64>,
+    #[schemars(description = "Whether to return the vectors of the points. Defaults to false")]
+    #[serde(default)]
+    pub with_vector: bool,
+    #[schemars(
+        description = "The payload fields to return. All payload fields are returned by default"
+    )]
+    #[serde(default)]
+    pub payload_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Prefetch {
+    #[schemars(description = "The dense or sparse vector to search for")]
+    pub query: QueryVector,
+    #[schemars(
+        description = "The name of the vector to search, for collections with named vectors"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub using: Option<String>,
+    #[schemars(description = "Filter conditions in the Qdrant filter format")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Value>,
+    #[schemars(description = "Maximum number of candidates to return")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum QueryVector {
+    #[schemars(description = "A dense vector")]
+    Dense(Vec<f32>),
+    #[schemars(description = "A sparse vector")]
+    Sparse(SparseVector),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SparseVector {
+    #[schemars(description = "The indices of the non-zero elements")]
+    pub indices: Vec<u32>,
+    #[schemars(description = "The values of the non-zero elements")]
+    pub values: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Fusion {
+    #[schemars(description = "Reciprocal rank fusion")]
+    Rrf,
+    #[schemars(description = "Distribution-based score fusion")]
+    Dbsf,
 }
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -127,12 +232,37 @@ impl From<CallToolResult> for SearchPointsResponse {
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ScoredPoint {
+    #[schemars(description = "The id of the point")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<PointId>,
     #[schemars(description = "The score of the point")]
     pub score: f64,
     #[schemars(description = "The payload of the point")]
+    #[serde(default)]
     pub payload: HashMap<String, Value>,
-    #[schemars(description = "The vector of the point")]
-    pub vector: Vec<f64>,
+    #[schemars(description = "The vectors of the point, if requested")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vectors>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Vectors {
+    #[schemars(description = "The unnamed vector of the point")]
+    Unnamed(Vector),
+    #[schemars(description = "The named vectors of the point")]
+    Named(HashMap<String, Vector>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Vector {
+    #[schemars(description = "A dense vector")]
+    Dense(Vec<f64>),
+    #[schemars(description = "A multi-vector")]
+    MultiDense(Vec<Vec<f64>>),
+    #[schemars(description = "A sparse vector")]
+    Sparse(SparseVector),
 }
 
 #[derive(Debug, Clone)]
@@ -140,3 +270,578 @@ pub struct ConnectionConfig {
     pub base_url: String,
     pub api_key: Option<String>,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum PointId {
+    #[schemars(description = "An unsigned integer id")]
+    Num(u64),
+    #[schemars(description = "A UUID")]
+    Uuid(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Record {
+    #[schemars(description = "The id of the point")]
+    pub id: PointId,
+    #[schemars(description = "The payload of the point")]
+    #[serde(default)]
+    pub payload: HashMap<String, Value>,
+    #[schemars(description = "The vectors of the point, if requested")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vectors>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetPointsRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(description = "The ids of the points to retrieve")]
+    pub ids: Vec<PointId>,
+    #[schemars(description = "Whether to return the vectors of the points. Defaults to false")]
+    #[serde(default)]
+    pub with_vector: bool,
+    #[schemars(
+        description = "The payload fields to return. All payload fields are returned by default"
+    )]
+    #[serde(default)]
+    pub payload_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct GetPointsResponse {
+    #[schemars(description = "The retrieved points. Points that do not exist are omitted")]
+    pub result: Vec<Record>,
+    #[schemars(description = "The time it took to retrieve the points")]
+    pub time: f64,
+}
+impl From<CallToolResult> for GetPointsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<GetPointsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ScrollPointsRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(description = "Filter conditions in the Qdrant filter format")]
+    #[serde(default)]
+    pub filter: Option<Value>,
+    #[schemars(
+        description = "Maximum number of points to return, greater than 0. Defaults to the limit configured on the server, and is capped at the maximum configured on the server"
+    )]
+    #[serde(default)]
+    pub limit: Option<u64>,
+    #[schemars(
+        description = "The cursor returned by the previous call, used to fetch the next page of points"
+    )]
+    #[serde(default)]
+    pub cursor: Option<PointId>,
+    #[schemars(description = "Whether to return the vectors of the points. Defaults to false")]
+    #[serde(default)]
+    pub with_vector: bool,
+    #[schemars(
+        description = "The payload fields to return. All payload fields are returned by default"
+    )]
+    #[serde(default)]
+    pub payload_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ScrollPointsResponse {
+    #[schemars(description = "The points of the page")]
+    pub points: Vec<Record>,
+    #[schemars(
+        description = "The cursor to fetch the next page of points. `None` if there are no more points"
+    )]
+    pub next_cursor: Option<PointId>,
+    #[schemars(description = "The time it took to scroll the points")]
+    pub time: f64,
+}
+impl From<CallToolResult> for ScrollPointsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<ScrollPointsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CountPointsRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(description = "Filter conditions in the Qdrant filter format")]
+    #[serde(default)]
+    pub filter: Option<Value>,
+    #[schemars(
+        description = "Whether to count the points exactly. Approximate counts are faster. Defaults to true"
+    )]
+    #[serde(default)]
+    pub exact: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CountPointsResponse {
+    #[schemars(description = "The number of points matching the filter")]
+    pub count: u64,
+    #[schemars(description = "The time it took to count the points")]
+    pub time: f64,
+}
+impl From<CallToolResult> for CountPointsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<CountPointsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Example {
+    #[schemars(description = "The id of a point in the collection")]
+    Id(PointId),
+    #[schemars(description = "A dense or sparse vector")]
+    Vector(QueryVector),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendStrategy {
+    #[schemars(
+        description = "Search with the average of the positive examples minus the average of the negative examples. Fast, works best with few examples"
+    )]
+    AverageVector,
+    #[schemars(
+        description = "Score each candidate against every example separately. Slower, but supports negative-only recommendations"
+    )]
+    BestScore,
+    #[schemars(
+        description = "Sum of the similarity scores to the positive examples minus the sum of the scores to the negative examples"
+    )]
+    SumScores,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RecommendPointsRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(description = "Point ids or vectors of examples to find similar points to")]
+    #[serde(default)]
+    pub positive: Vec<Example>,
+    #[schemars(description = "Point ids or vectors of examples to find dissimilar points to")]
+    #[serde(default)]
+    pub negative: Vec<Example>,
+    #[schemars(
+        description = "The strategy used to combine the examples. Defaults to `average_vector`"
+    )]
+    #[serde(default)]
+    pub strategy: Option<RecommendStrategy>,
+    #[schemars(
+        description = "The name of the vector to search, for collections with named vectors"
+    )]
+    #[serde(default)]
+    pub using: Option<String>,
+    #[schemars(description = "Filter conditions in the Qdrant filter format")]
+    #[serde(default)]
+    pub filter: Option<Value>,
+    #[schemars(
+        description = "Maximum number of results to return, greater than 0. Defaults to the limit configured on the server, and is capped at the maximum configured on the server"
+    )]
+    #[serde(default)]
+    pub limit: Option<u64>,
+    #[schemars(description = "Whether to return the vectors of the points. Defaults to false")]
+    #[serde(default)]
+    pub with_vector: bool,
+    #[schemars(
+        description = "The payload fields to return. All payload fields are returned by default"
+    )]
+    #[serde(default)]
+    pub payload_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ContextPair {
+    #[schemars(description = "Point id or vector of an example the results should be close to")]
+    pub positive: Example,
+    #[schemars(description = "Point id or vector of an example the results should be far from")]
+    pub negative: Example,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DiscoverPointsRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(
+        description = "Point id or vector of the target. If omitted, only the context is used to explore the space"
+    )]
+    #[serde(default)]
+    pub target: Option<Example>,
+    #[schemars(
+        description = "Pairs of positive and negative examples that split the space into preferred and avoided zones"
+    )]
+    pub context: Vec<ContextPair>,
+    #[schemars(
+        description = "The name of the vector to search, for collections with named vectors"
+    )]
+    #[serde(default)]
+    pub using: Option<String>,
+    #[schemars(description = "Filter conditions in the Qdrant filter format")]
+    #[serde(default)]
+    pub filter: Option<Value>,
+    #[schemars(
+        description = "Maximum number of results to return, greater than 0. Defaults to the limit configured on the server, and is capped at the maximum configured on the server"
+    )]
+    #[serde(default)]
+    pub limit: Option<u64>,
+    #[schemars(description = "Whether to return the vectors of the points. Defaults to false")]
+    #[serde(default)]
+    pub with_vector: bool,
+    #[schemars(
+        description = "The payload fields to return. All payload fields are returned by default"
+    )]
+    #[serde(default)]
+    pub payload_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DescribeCollectionsResponse {
+    #[schemars(description = "The collections the server is allowed to access")]
+    pub collections: Vec<CollectionDescription>,
+}
+impl From<CallToolResult> for DescribeCollectionsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<DescribeCollectionsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CollectionDescription {
+    #[schemars(description = "The name of the collection")]
+    pub name: String,
+    #[schemars(description = "The status of the collection: 'green', 'yellow', 'grey' or 'red'")]
+    pub status: String,
+    #[schemars(description = "The approximate number of points in the collection")]
+    pub points_count: Option<u64>,
+    #[schemars(description = "The dense vectors of the collection")]
+    pub vectors: Vec<VectorDescription>,
+    #[schemars(description = "The names of the sparse vectors of the collection")]
+    pub sparse_vectors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct VectorDescription {
+    #[schemars(description = "The name of the vector. `None` for the unnamed vector")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[schemars(description = "The size of the vector")]
+    pub size: u64,
+    #[schemars(description = "The distance function: 'Cosine', 'Euclid', 'Dot' or 'Manhattan'")]
+    pub distance: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadSchemaType {
+    #[schemars(description = "Exact match on string values")]
+    Keyword,
+    #[schemars(description = "Exact match and range filters on integer values")]
+    Integer,
+    #[schemars(description = "Range filters on float values")]
+    Float,
+    #[schemars(description = "Geo filters on geo points")]
+    Geo,
+    #[schemars(description = "Full-text match on string values")]
+    Text,
+    #[schemars(description = "Exact match on boolean values")]
+    Bool,
+    #[schemars(description = "Range filters on RFC 3339 datetime values")]
+    Datetime,
+    #[schemars(description = "Exact match on UUID values")]
+    Uuid,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CreatePayloadIndexRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(
+        description = "The name of the payload field to index. Use dots for nested fields, e.g. `metadata.author`"
+    )]
+    pub field_name: String,
+    #[schemars(description = "The type of the payload field")]
+    pub field_schema: PayloadSchemaType,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DeletePayloadIndexRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(description = "The name of the indexed payload field")]
+    pub field_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UpdateOperationResponse {
+    #[schemars(description = "The sequential number of the operation")]
+    pub operation_id: Option<u64>,
+    #[schemars(
+        description = "The status of the operation. Allowed values: 'acknowledged', 'completed'"
+    )]
+    pub status: String,
+    #[schemars(description = "The time it took to perform the operation")]
+    pub time: f64,
+}
+impl From<CallToolResult> for UpdateOperationResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<UpdateOperationResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct HnswConfigDiff {
+    #[schemars(description = "Number of edges per node in the index graph")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m: Option<u64>,
+    #[schemars(description = "Number of neighbours to consider during the index building")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ef_construct: Option<u64>,
+    #[schemars(
+        description = "Minimal size (in kilobytes) of vectors for additional payload-based indexing"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_scan_threshold: Option<u64>,
+    #[schemars(
+        description = "Number of parallel threads used for background index building. 0 selects automatically"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_indexing_threads: Option<u64>,
+    #[schemars(description = "Whether to store the index on disk instead of in memory")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_disk: Option<bool>,
+    #[schemars(description = "Number of edges per node in the payload-based index graph")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_m: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct OptimizersConfigDiff {
+    #[schemars(
+        description = "Minimal fraction of deleted vectors in a segment required to vacuum it"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_threshold: Option<f64>,
+    #[schemars(description = "Minimal number of vectors in a segment required to vacuum it")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vacuum_min_vector_number: Option<u64>,
+    #[schemars(description = "Target number of segments. 0 selects automatically")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_segment_number: Option<u64>,
+    #[schemars(description = "Maximum size (in kilobytes) of a segment")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_segment_size: Option<u64>,
+    #[schemars(
+        description = "Maximum size (in kilobytes) of vectors to store in memory per segment; larger segments are memory-mapped"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memmap_threshold: Option<u64>,
+    #[schemars(
+        description = "Maximum size (in kilobytes) of vectors allowed for plain index; larger segments are indexed"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexing_threshold: Option<u64>,
+    #[schemars(description = "Minimum interval between forced flushes, in seconds")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flush_interval_sec: Option<u64>,
+    #[schemars(
+        description = "Maximum number of threads used by the optimizers. 0 disables optimizations"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_optimization_threads: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UpdateCollectionRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(description = "The HNSW index parameters to change")]
+    #[serde(default)]
+    pub hnsw_config: Option<HnswConfigDiff>,
+    #[schemars(description = "The optimizer parameters to change")]
+    #[serde(default)]
+    pub optimizers_config: Option<OptimizersConfigDiff>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UpdateCollectionResponse {
+    #[schemars(description = "Whether the operation made changes")]
+    pub result: bool,
+    #[schemars(description = "The time it took to update the collection")]
+    pub time: f64,
+}
+impl From<CallToolResult> for UpdateCollectionResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<UpdateCollectionResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SnapshotRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SnapshotDescription {
+    #[schemars(description = "The name of the snapshot")]
+    pub name: String,
+    #[schemars(description = "The time the snapshot was created")]
+    #[serde(default)]
+    pub creation_time: Option<String>,
+    #[schemars(description = "The size of the snapshot in bytes")]
+    pub size: u64,
+    #[schemars(description = "The SHA-256 checksum of the snapshot")]
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CreateSnapshotResponse {
+    #[schemars(description = "The created snapshot")]
+    pub result: SnapshotDescription,
+    #[schemars(description = "The time it took to create the snapshot")]
+    pub time: f64,
+}
+impl From<CallToolResult> for CreateSnapshotResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<CreateSnapshotResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ListSnapshotsResponse {
+    #[schemars(description = "The snapshots of the collection")]
+    pub snapshots: Vec<SnapshotDescription>,
+    #[schemars(description = "The time it took to list the snapshots")]
+    pub time: f64,
+}
+impl From<CallToolResult> for ListSnapshotsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<ListSnapshotsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotPriority {
+    #[schemars(
+        description = "Restore the snapshot without any additional synchronization with other replicas"
+    )]
+    NoSync,
+    #[schemars(description = "Prefer the data of the snapshot over the data of other replicas")]
+    Snapshot,
+    #[schemars(description = "Prefer the data of other replicas over the data of the snapshot")]
+    Replica,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RestoreSnapshotRequest {
+    #[schemars(
+        description = "The name of the collection. Defaults to the collection configured on the server"
+    )]
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[schemars(
+        description = "The location of the snapshot: a URL, or a `file:///` path on the Qdrant server"
+    )]
+    pub location: String,
+    #[schemars(description = "How to resolve conflicts with the data of other replicas")]
+    #[serde(default)]
+    pub priority: Option<SnapshotPriority>,
+    #[schemars(
+        description = "The SHA-256 checksum of the snapshot, verified before the snapshot is restored"
+    )]
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct RestoreSnapshotResponse {
+    #[schemars(description = "Whether the snapshot was restored")]
+    pub result: bool,
+    #[schemars(description = "The time it took to restore the snapshot")]
+    pub time: f64,
+}
+impl From<CallToolResult> for RestoreSnapshotResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<RestoreSnapshotResponse>(&content).unwrap()
+    }
+}
@@ -4,7 +4,8 @@ use clap::{Parser, ValueEnum};
 use once_cell::sync::OnceCell;
 use qdrant::QdrantServer;
 use rmcp::{
-    ServiceExt,
+    ErrorData as McpError, ServiceExt,
+    model::ErrorCode,
     transport::{
         sse_server::SseServer,
         stdio,
@@ -28,15 +29,25 @@ struct Args {
     /// Transport type to use
     #[arg(short, long, value_enum, default_value = "stream-http")]
     transport: TransportType,
-    /// Name of the collection to search
-    #[arg(long, required = true)]
-    collection: String,
-    /// Maximum number of results to return
+    /// Name of the default collection, used when a tool call does not specify one
+    #[arg(long, required_unless_present = "allowed_collections")]
+    collection: Option<String>,
+    /// Names or glob patterns (e.g. `docs-*`) of further collections the tools are allowed to access
+    #[arg(long, value_delimiter = ',')]
+    allowed_collections: Vec<String>,
+    /// Maximum number of results to return when a tool call does not specify `limit`
     #[arg(long, default_value = "10")]
     limit: u64,
+    /// Upper bound for the `limit` of a tool call
+    #[arg(long, default_value = "100")]
+    max_limit: u64,
     /// Score threshold for the results
     #[arg(long, default_value = "0.5")]
     score_threshold: f32,
+    /// Enable the tools that modify collections: payload indexes, collection parameters and
+    /// snapshots
+    #[arg(long)]
+    enable_admin_tools: bool,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -58,6 +69,13 @@ async fn main() -> anyhow::Result<()> {
 
     let args = Args::parse();
 
+    if args.limit == 0 || args.limit > args.max_limit {
+        anyhow::bail!(
+            "--limit must be between 1 and --max-limit ({})",
+            args.max_limit
+        );
+    }
+
     let base_url = std::env::var("QDRANT_BASE_URL").unwrap_or(DEFAULT_QDRANT_BASE_URL.to_string());
 
     // parse api key
@@ -67,7 +85,9 @@ async fn main() -> anyhow::Result<()> {
         base_url,
         api_key,
         collection: args.collection,
+        allowed_collections: args.allowed_collections,
         limit: args.limit,
+        max_limit: args.max_limit,
         score_threshold: args.score_threshold,
     };
 
@@ -80,7 +100,7 @@ async fn main() -> anyhow::Result<()> {
     match args.transport {
         TransportType::StreamHttp => {
             let service = StreamableHttpService::new(
-                || Ok(QdrantServer::new()),
+                move || Ok(QdrantServer::new(args.enable_admin_tools)),
                 LocalSessionManager::default().into(),
                 Default::default(),
             );
@@ -94,16 +114,19 @@ async fn main() -> anyhow::Result<()> {
         TransportType::Sse => {
             let ct = SseServer::serve(args.socket_addr.parse()?)
                 .await?
-                .with_service(QdrantServer::new);
+                .with_service(move || QdrantServer::new(args.enable_admin_tools));
 
             tokio::signal::ctrl_c().await?;
             ct.cancel();
         }
         TransportType::Stdio => {
             // Create an instance of our counter router
-            let service = QdrantServer::new().serve(stdio()).await.inspect_err(|e| {
-                tracing::error!("serving error: {:?}", e);
-            })?;
+            let service = QdrantServer::new(args.enable_admin_tools)
+                .serve(stdio())
+                .await
+                .inspect_err(|e| {
+                    tracing::error!("serving error: {:?}", e);
+                })?;
 
             service.waiting().await?;
         }
@@ -116,7 +139,210 @@ async fn main() -> anyhow::Result<()> {
 pub struct ConnectionConfig {
     pub base_url: String,
     pub api_key: Option<String>,
-    pub collection: String,
+    /// default collection
+    pub collection: Option<String>,
+    /// names or glob patterns of further allowed collections
+    pub allowed_collections: Vec<String>,
+    /// default number of results
     pub limit: u64,
+    /// upper bound for the number of results
+    pub max_limit: u64,
     pub score_threshold: f32,
 }
+impl ConnectionConfig {
+    /// Check whether the tools are allowed to access the given collection.
+    pub fn is_collection_allowed(&self, name: &str) -> bool {
+        self.collection.as_deref() == Some(name)
+            || self
+                .allowed_collections
+                .iter()
+                .any(|pattern| glob_match(pattern, name))
+    }
+
+    /// Resolve the collection of a tool call, falling back to the default collection.
+    pub fn resolve_collection(&self, collection: Option<String>) -> Result<String, McpError> {
+        match collection.or_else(|| self.collection.clone()) {
+            // checked before the allow-list, as `*` would match e.g. `docs-x/../secret`
+            Some(name) if !is_valid_collection_name(&name) => {
+                let error_message = format!("Invalid collection name `{name}`");
+                tracing::error!("{}", error_message);
+                Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ))
+            }
+            Some(name) if self.is_collection_allowed(&name) => Ok(name),
+            Some(name) => {
+                let error_message = format!("Access to the collection `{name}` is not allowed");
+                tracing::error!("{}", error_message);
+                Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ))
+            }
+            None => {
+                let error_message = "No collection specified and no default collection configured";
+                tracing::error!("{}", error_message);
+                Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ))
+            }
+        }
+    }
+
+    /// Resolve the number of results of a tool call: the default limit if not set, and at most the
+    /// maximum limit.
+    pub fn resolve_limit(&self, limit: Option<u64>) -> Result<u64, McpError> {
+        match limit {
+            Some(0) => {
+                let error_message = "`limit` must be greater than 0";
+                tracing::error!("{}", error_message);
+                Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ))
+            }
+            Some(limit) if limit > self.max_limit => {
+                tracing::warn!(
+                    "`limit` ({}) exceeds the maximum ({}), capping it",
+                    limit,
+                    self.max_limit
+                );
+                Ok(self.max_limit)
+            }
+            Some(limit) => Ok(limit),
+            None => Ok(self.limit),
+        }
+    }
+}
+
+/// Check that a collection name can be used as a single segment of a URL path.
+fn is_valid_collection_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains("..") && !name.contains(['/', '\\', '?', '#', '%'])
+}
+
+/// Match a name against a glob pattern supporting `*` (any sequence) and `?` (any character).
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` in the pattern and the name position it matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conn_config(collection: Option<&str>, allowed_collections: &[&str]) -> ConnectionConfig {
+        ConnectionConfig {
+            base_url: DEFAULT_QDRANT_BASE_URL.to_string(),
+            api_key: None,
+            collection: collection.map(String::from),
+            allowed_collections: allowed_collections.iter().map(|s| s.to_string()).collect(),
+            limit: 10,
+            max_limit: 100,
+            score_threshold: 0.5,
+        }
+    }
+
+    #[test]
+    fn glob_match_wildcards() {
+        assert!(glob_match("docs", "docs"));
+        assert!(!glob_match("docs", "docs-1"));
+        assert!(glob_match("docs-*", "docs-"));
+        assert!(glob_match("docs-*", "docs-2024"));
+        assert!(!glob_match("docs-*", "faq-2024"));
+        assert!(glob_match("docs-?", "docs-a"));
+        assert!(!glob_match("docs-?", "docs-ab"));
+        assert!(glob_match("*-prod-*", "docs-prod-v2"));
+        assert!(!glob_match("*-prod-*", "docs-dev-v2"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("", "docs"));
+    }
+
+    #[test]
+    fn resolve_allowed_collections() {
+        let conn_config = conn_config(Some("main"), &["docs-*", "faq"]);
+
+        assert_eq!(conn_config.resolve_collection(None).unwrap(), "main");
+        for name in ["main", "faq", "docs-2024"] {
+            assert_eq!(
+                conn_config.resolve_collection(Some(name.into())).unwrap(),
+                name
+            );
+        }
+        for name in ["secret", "docs", "faq-2024"] {
+            let error = conn_config
+                .resolve_collection(Some(name.into()))
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::INVALID_PARAMS, "{name}");
+        }
+
+        // no default collection
+        let conn_config = self::conn_config(None, &["docs-*"]);
+        assert!(conn_config.resolve_collection(None).is_err());
+    }
+
+    #[test]
+    fn resolve_collection_rejects_path_traversal() {
+        let conn_config = conn_config(Some("main"), &["docs-*", "*"]);
+
+        for name in [
+            "docs-x/../secret",
+            "docs-x/..",
+            "..",
+            "docs-x%2F..%2Fsecret",
+            "docs-x?wait=true",
+            "docs-x#fragment",
+            "docs-x\\..\\secret",
+            "",
+        ] {
+            let error = conn_config
+                .resolve_collection(Some(name.into()))
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::INVALID_PARAMS, "{name}");
+        }
+    }
+
+    #[test]
+    fn resolve_limit_is_bounded() {
+        let conn_config = conn_config(Some("main"), &[]);
+
+        assert_eq!(conn_config.resolve_limit(None).unwrap(), 10);
+        assert_eq!(conn_config.resolve_limit(Some(20)).unwrap(), 20);
+        assert_eq!(conn_config.resolve_limit(Some(u64::MAX)).unwrap(), 100);
+        assert_eq!(
+            conn_config.resolve_limit(Some(0)).unwrap_err().code,
+            ErrorCode::INVALID_PARAMS
+        );
+    }
+}
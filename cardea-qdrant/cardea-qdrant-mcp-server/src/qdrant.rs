@@ -1,5 +1,6 @@
-use crate::CONNECTION_CONFIG;
+use crate::{CONNECTION_CONFIG, ConnectionConfig};
 use cardea_qdrant_mcp_common::*;
+use reqwest::{Method, Url};
 use rmcp::{
     ErrorData as McpError, RoleServer, ServerHandler,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
@@ -7,25 +8,48 @@ use rmcp::{
     service::RequestContext,
     tool, tool_handler, tool_router,
 };
+use serde::{Deserialize, de::DeserializeOwned};
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use tracing::error;
 
+/// Tools that modify collections, only available with `--enable-admin-tools`
+const ADMIN_TOOLS: [&str; 6] = [
+    "create_payload_index",
+    "delete_payload_index",
+    "update_collection",
+    "create_snapshot",
+    "list_snapshots",
+    "restore_snapshot",
+];
+
 #[derive(Debug, Clone)]
 pub struct QdrantServer {
     tool_router: ToolRouter<Self>,
 }
 #[tool_router]
 impl QdrantServer {
-    pub fn new() -> Self {
-        Self {
-            tool_router: Self::tool_router(),
+    pub fn new(enable_admin_tools: bool) -> Self {
+        let mut tool_router = Self::tool_router();
+        if !enable_admin_tools {
+            for name in ADMIN_TOOLS {
+                tool_router.remove_route(name);
+            }
         }
+
+        Self { tool_router }
     }
 
     #[tool(description = "Perform vector search in the Qdrant database")]
     async fn search(
         &self,
-        Parameters(SearchPointsRequest { vector }): Parameters<SearchPointsRequest>,
+        Parameters(SearchPointsRequest {
+            collection,
+            vector,
+            using,
+            with_vector,
+            payload_fields,
+        }): Parameters<SearchPointsRequest>,
     ) -> Result<CallToolResult, McpError> {
         // get connection config
         let conn_config = match CONNECTION_CONFIG.get() {
@@ -44,53 +68,137 @@ impl QdrantServer {
             }
         };
 
-        // build url
-        let base_url = conn_config.base_url.trim_end_matches('/');
-        let url = format!(
-            "{}/collections/{}/points/search",
-            base_url, conn_config.collection
-        );
-
-        // get api key
-        let api_key = conn_config.api_key;
+        let collection = conn_config.resolve_collection(collection)?;
 
         // build params
-        let params = json!({
-            "vector": vector,
+        let mut params = json!({
+            "query": vector,
             "limit": conn_config.limit,
-            "with_payload": true,
-            "with_vector": true,
+            "with_payload": with_payload(payload_fields),
+            "with_vector": with_vector,
             "score_threshold": conn_config.score_threshold,
         });
+        if let Some(using) = using {
+            params["using"] = json!(using);
+        }
+
+        query_points(&conn_config, &collection, &params).await
+    }
 
-        let client = reqwest::Client::new();
-        let result = match api_key {
-            Some(api_key) => {
-                client
-                    .post(&url)
-                    .header("api-key", api_key)
-                    .header("Content-Type", "application/json")
-                    .json(&params)
-                    .send()
-                    .await
+    #[tool(
+        description = "Perform a universal query in the Qdrant database. Supports dense and sparse vectors, named vectors, and hybrid search by fusing the results of several prefetch queries"
+    )]
+    async fn query(
+        &self,
+        Parameters(QueryPointsRequest {
+            collection,
+            query,
+            using,
+            prefetch,
+            fusion,
+            filter,
+            limit,
+            with_vector,
+            payload_fields,
+        }): Parameters<QueryPointsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
             }
             None => {
-                client
-                    .post(&url)
-                    .header("Content-Type", "application/json")
-                    .json(&params)
-                    .send()
-                    .await
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
             }
         };
 
-        let response = match result {
-            Ok(response) => response,
-            Err(e) => {
-                let error_message = format!("Failed to search points: {e}");
+        let collection = conn_config.resolve_collection(collection)?;
 
+        // fused results do not search a vector themselves, the prefetch queries do
+        if fusion.is_some() && using.is_some() {
+            let error_message =
+                "`using` cannot be combined with `fusion`, set it on the prefetch queries instead";
+            error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+
+        let query = match (query, &fusion) {
+            (Some(query), None) => json!(query),
+            (None, Some(fusion)) if !prefetch.is_empty() => json!({ "fusion": fusion }),
+            (None, Some(_)) => {
+                let error_message = "`fusion` requires at least one prefetch query";
                 error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+            _ => {
+                let error_message = "Exactly one of `query` and `fusion` must be provided";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        // build params
+        let mut params = json!({
+            "query": query,
+            "limit": conn_config.resolve_limit(limit)?,
+            "with_payload": with_payload(payload_fields),
+            "with_vector": with_vector,
+        });
+        // fused scores are not comparable to similarity scores
+        if fusion.is_none() {
+            params["score_threshold"] = json!(conn_config.score_threshold);
+        }
+        if let Some(using) = using {
+            params["using"] = json!(using);
+        }
+        if !prefetch.is_empty() {
+            params["prefetch"] = json!(prefetch);
+        }
+        if let Some(filter) = filter {
+            params["filter"] = filter;
+        }
+
+        query_points(&conn_config, &collection, &params).await
+    }
 
+    #[tool(description = "Retrieve points from the Qdrant database by their ids")]
+    async fn get_points(
+        &self,
+        Parameters(GetPointsRequest {
+            collection,
+            ids,
+            with_vector,
+            payload_fields,
+        }): Parameters<GetPointsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
                 return Err(McpError::new(
                     ErrorCode::INTERNAL_ERROR,
                     error_message,
@@ -99,83 +207,687 @@ impl QdrantServer {
             }
         };
 
-        match response.status().is_success() {
-            true => match response.json::<Value>().await {
-                Ok(json) => match json.get("result") {
-                    Some(result) => {
-                        let scored_points = result
-                            .as_array()
-                            .unwrap()
-                            .iter()
-                            .map(|v| ScoredPoint {
-                                score: v.get("score").unwrap().as_f64().unwrap(),
-                                payload: v
-                                    .get("payload")
-                                    .unwrap()
-                                    .as_object()
-                                    .unwrap()
-                                    .to_owned()
-                                    .into_iter()
-                                    .map(|(k, v)| (k.to_string(), v.clone()))
-                                    .collect(),
-                                vector: v
-                                    .get("vector")
-                                    .unwrap()
-                                    .as_array()
-                                    .unwrap()
-                                    .to_owned()
-                                    .iter()
-                                    .map(|v| v.as_f64().unwrap())
-                                    .collect::<Vec<f64>>(),
-                            })
-                            .collect();
-
-                        let content = Content::json(SearchPointsResponse {
-                            result: scored_points,
-                            time: json.get("time").unwrap().as_f64().unwrap(),
-                        })?;
-
-                        Ok(CallToolResult::success(vec![content]))
-                    }
-                    None => {
-                        let error_message =
-                            "Failed to search points. The given key 'result' does not exist.";
-
-                        error!("{}", error_message);
-
-                        Err(McpError::new(
-                            ErrorCode::INTERNAL_ERROR,
-                            error_message,
-                            None,
-                        ))
-                    }
-                },
-                Err(e) => {
-                    let error_message = format!("Failed to search points: {e}");
+        let collection = conn_config.resolve_collection(collection)?;
 
-                    error!("{}", error_message);
+        // build params
+        let params = json!({
+            "ids": ids,
+            "with_payload": with_payload(payload_fields),
+            "with_vector": with_vector,
+        });
 
-                    Err(McpError::new(
-                        ErrorCode::INTERNAL_ERROR,
-                        error_message,
-                        None,
-                    ))
-                }
-            },
-            false => {
-                let error_message = format!("Failed to search points: {}", response.status());
+        let response: QdrantResponse<Vec<Record>> = send_request(
+            &conn_config,
+            Method::POST,
+            &["collections", &collection, "points"],
+            &[],
+            Some(&params),
+            "retrieve points",
+        )
+        .await?;
+
+        let content = Content::json(GetPointsResponse {
+            result: response.result,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
 
+    #[tool(
+        description = "Scroll through the points of the Qdrant database page by page, optionally filtered. If more points are available, the response contains a cursor that can be passed to a follow-up call to fetch the next page"
+    )]
+    async fn scroll_points(
+        &self,
+        Parameters(ScrollPointsRequest {
+            collection,
+            filter,
+            limit,
+            cursor,
+            with_vector,
+            payload_fields,
+        }): Parameters<ScrollPointsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
                 error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        // build params
+        let mut params = json!({
+            "limit": conn_config.resolve_limit(limit)?,
+            "with_payload": with_payload(payload_fields),
+            "with_vector": with_vector,
+        });
+        if let Some(filter) = filter {
+            params["filter"] = filter;
+        }
+        if let Some(cursor) = cursor {
+            params["offset"] = json!(cursor);
+        }
+
+        let response: QdrantResponse<ScrollResult> = send_request(
+            &conn_config,
+            Method::POST,
+            &["collections", &collection, "points", "scroll"],
+            &[],
+            Some(&params),
+            "scroll points",
+        )
+        .await?;
+
+        let content = Content::json(ScrollPointsResponse {
+            points: response.result.points,
+            next_cursor: response.result.next_page_offset,
+            time: response.time,
+        })?;
 
-                Err(McpError::new(
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "Count the points of the Qdrant database, optionally filtered")]
+    async fn count_points(
+        &self,
+        Parameters(CountPointsRequest {
+            collection,
+            filter,
+            exact,
+        }): Parameters<CountPointsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
                     ErrorCode::INTERNAL_ERROR,
                     error_message,
                     None,
-                ))
+                ));
             }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        // build params
+        let mut params = json!({
+            "exact": exact.unwrap_or(true),
+        });
+        if let Some(filter) = filter {
+            params["filter"] = filter;
         }
+
+        let response: QdrantResponse<CountResult> = send_request(
+            &conn_config,
+            Method::POST,
+            &["collections", &collection, "points", "count"],
+            &[],
+            Some(&params),
+            "count points",
+        )
+        .await?;
+
+        let content = Content::json(CountPointsResponse {
+            count: response.result.count,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Recommend points similar to the positive examples and dissimilar to the negative examples. Examples are point ids or vectors"
+    )]
+    async fn recommend(
+        &self,
+        Parameters(RecommendPointsRequest {
+            collection,
+            positive,
+            negative,
+            strategy,
+            using,
+            filter,
+            limit,
+            with_vector,
+            payload_fields,
+        }): Parameters<RecommendPointsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        if positive.is_empty() && negative.is_empty() {
+            let error_message = "At least one positive or negative example is required";
+            error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+
+        let mut recommend = json!({
+            "positive": positive,
+            "negative": negative,
+        });
+        if let Some(strategy) = strategy {
+            recommend["strategy"] = json!(strategy);
+        }
+        let query = json!({ "recommend": recommend });
+
+        // build params
+        let mut params = json!({
+            "query": query,
+            "limit": conn_config.resolve_limit(limit)?,
+            "with_payload": with_payload(payload_fields),
+            "with_vector": with_vector,
+        });
+        if let Some(using) = using {
+            params["using"] = json!(using);
+        }
+        if let Some(filter) = filter {
+            params["filter"] = filter;
+        }
+
+        query_points(&conn_config, &collection, &params).await
+    }
+
+    #[tool(
+        description = "Discover points close to the target and in the preferred zones defined by the context pairs of positive and negative examples. Examples are point ids or vectors"
+    )]
+    async fn discover(
+        &self,
+        Parameters(DiscoverPointsRequest {
+            collection,
+            target,
+            context,
+            using,
+            filter,
+            limit,
+            with_vector,
+            payload_fields,
+        }): Parameters<DiscoverPointsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        if context.is_empty() {
+            let error_message = "At least one context pair is required";
+            error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+
+        // without a target, the context search explores the preferred zones only
+        let query = match target {
+            Some(target) => json!({ "discover": { "target": target, "context": context } }),
+            None => json!({ "context": context }),
+        };
+
+        // build params
+        let mut params = json!({
+            "query": query,
+            "limit": conn_config.resolve_limit(limit)?,
+            "with_payload": with_payload(payload_fields),
+            "with_vector": with_vector,
+        });
+        if let Some(using) = using {
+            params["using"] = json!(using);
+        }
+        if let Some(filter) = filter {
+            params["filter"] = filter;
+        }
+
+        query_points(&conn_config, &collection, &params).await
+    }
+
+    #[tool(
+        description = "Describe the collections the server is allowed to access, including the size and distance of their vectors, their sparse vectors and their number of points"
+    )]
+    async fn describe_collections(&self) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let response: QdrantResponse<CollectionsResult> = send_request(
+            &conn_config,
+            Method::GET,
+            &["collections"],
+            &[],
+            None,
+            "list collections",
+        )
+        .await?;
+
+        let mut collections = Vec::new();
+        for CollectionName { name } in response.result.collections {
+            if !conn_config.is_collection_allowed(&name) {
+                continue;
+            }
+
+            let response: QdrantResponse<CollectionInfo> = send_request(
+                &conn_config,
+                Method::GET,
+                &["collections", &name],
+                &[],
+                None,
+                "get collection info",
+            )
+            .await?;
+            let info = response.result;
+
+            let vectors = match info.config.params.vectors {
+                Some(VectorsConfig::Unnamed(params)) => vec![VectorDescription {
+                    name: None,
+                    size: params.size,
+                    distance: params.distance,
+                }],
+                Some(VectorsConfig::Named(params)) => params
+                    .into_iter()
+                    .map(|(name, params)| VectorDescription {
+                        name: Some(name),
+                        size: params.size,
+                        distance: params.distance,
+                    })
+                    .collect(),
+                None => vec![],
+            };
+
+            collections.push(CollectionDescription {
+                name,
+                status: info.status,
+                points_count: info.points_count,
+                vectors,
+                sparse_vectors: info
+                    .config
+                    .params
+                    .sparse_vectors
+                    .unwrap_or_default()
+                    .into_keys()
+                    .collect(),
+            });
+        }
+        collections.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let content = Content::json(DescribeCollectionsResponse { collections })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "Create an index on a payload field to speed up filtering on it")]
+    async fn create_payload_index(
+        &self,
+        Parameters(CreatePayloadIndexRequest {
+            collection,
+            field_name,
+            field_schema,
+        }): Parameters<CreatePayloadIndexRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        // build params
+        let params = json!({
+            "field_name": field_name,
+            "field_schema": field_schema,
+        });
+
+        let response: QdrantResponse<UpdateResult> = send_request(
+            &conn_config,
+            Method::PUT,
+            &["collections", &collection, "index"],
+            &[("wait", "true")],
+            Some(&params),
+            "create payload index",
+        )
+        .await?;
+
+        let content = Content::json(UpdateOperationResponse {
+            operation_id: response.result.operation_id,
+            status: response.result.status,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "Delete the index of a payload field")]
+    async fn delete_payload_index(
+        &self,
+        Parameters(DeletePayloadIndexRequest {
+            collection,
+            field_name,
+        }): Parameters<DeletePayloadIndexRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        let response: QdrantResponse<UpdateResult> = send_request(
+            &conn_config,
+            Method::DELETE,
+            &["collections", &collection, "index", &field_name],
+            &[("wait", "true")],
+            None,
+            "delete payload index",
+        )
+        .await?;
+
+        let content = Content::json(UpdateOperationResponse {
+            operation_id: response.result.operation_id,
+            status: response.result.status,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Update the parameters of a collection: the HNSW index config and the optimizers config. Only the provided parameters are changed"
+    )]
+    async fn update_collection(
+        &self,
+        Parameters(UpdateCollectionRequest {
+            collection,
+            hnsw_config,
+            optimizers_config,
+        }): Parameters<UpdateCollectionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        if hnsw_config.is_none() && optimizers_config.is_none() {
+            let error_message = "Either `hnsw_config` or `optimizers_config` must be provided";
+            error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+
+        // build params
+        let mut params = json!({});
+        if let Some(hnsw_config) = hnsw_config {
+            params["hnsw_config"] = json!(hnsw_config);
+        }
+        if let Some(optimizers_config) = optimizers_config {
+            params["optimizers_config"] = json!(optimizers_config);
+        }
+
+        let response: QdrantResponse<bool> = send_request(
+            &conn_config,
+            Method::PATCH,
+            &["collections", &collection],
+            &[],
+            Some(&params),
+            "update collection",
+        )
+        .await?;
+
+        let content = Content::json(UpdateCollectionResponse {
+            result: response.result,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "Create a snapshot of a collection")]
+    async fn create_snapshot(
+        &self,
+        Parameters(SnapshotRequest { collection }): Parameters<SnapshotRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        let response: QdrantResponse<SnapshotDescription> = send_request(
+            &conn_config,
+            Method::POST,
+            &["collections", &collection, "snapshots"],
+            &[("wait", "true")],
+            None,
+            "create snapshot",
+        )
+        .await?;
+
+        let content = Content::json(CreateSnapshotResponse {
+            result: response.result,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "List the snapshots of a collection")]
+    async fn list_snapshots(
+        &self,
+        Parameters(SnapshotRequest { collection }): Parameters<SnapshotRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        let response: QdrantResponse<Vec<SnapshotDescription>> = send_request(
+            &conn_config,
+            Method::GET,
+            &["collections", &collection, "snapshots"],
+            &[],
+            None,
+            "list snapshots",
+        )
+        .await?;
+
+        let content = Content::json(ListSnapshotsResponse {
+            snapshots: response.result,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Restore a collection from a snapshot. The snapshot is given by a URL or a `file:///` path on the Qdrant server"
+    )]
+    async fn restore_snapshot(
+        &self,
+        Parameters(RestoreSnapshotRequest {
+            collection,
+            location,
+            priority,
+            checksum,
+        }): Parameters<RestoreSnapshotRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // get connection config
+        let conn_config = match CONNECTION_CONFIG.get() {
+            Some(connection_config) => {
+                let conn_config = connection_config.read().await;
+                conn_config.clone()
+            }
+            None => {
+                let error_message = "Connection config not found";
+                error!("{}", error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let collection = conn_config.resolve_collection(collection)?;
+
+        // build params
+        let mut params = json!({ "location": location });
+        if let Some(priority) = priority {
+            params["priority"] = json!(priority);
+        }
+        if let Some(checksum) = checksum {
+            params["checksum"] = json!(checksum);
+        }
+
+        let response: QdrantResponse<bool> = send_request(
+            &conn_config,
+            Method::PUT,
+            &["collections", &collection, "snapshots", "recover"],
+            &[("wait", "true")],
+            Some(&params),
+            "restore snapshot",
+        )
+        .await?;
+
+        let content = Content::json(RestoreSnapshotResponse {
+            result: response.result,
+            time: response.time,
+        })?;
+
+        Ok(CallToolResult::success(vec![content]))
     }
 }
+
 #[tool_handler]
 impl ServerHandler for QdrantServer {
     fn get_info(&self) -> ServerInfo {
@@ -241,3 +953,483 @@ impl ServerHandler for QdrantServer {
         }
     }
 }
+
+/// Build the `with_payload` parameter: all payload fields, or only the selected ones.
+fn with_payload(payload_fields: Option<Vec<String>>) -> Value {
+    match payload_fields {
+        Some(fields) => json!(fields),
+        None => json!(true),
+    }
+}
+
+/// Send a request to the query API of the given collection and return the scored points.
+async fn query_points(
+    conn_config: &ConnectionConfig,
+    collection: &str,
+    params: &Value,
+) -> Result<CallToolResult, McpError> {
+    let query_response: QdrantResponse<QueryResult> = send_request(
+        conn_config,
+        Method::POST,
+        &["collections", collection, "points", "query"],
+        &[],
+        Some(params),
+        "search points",
+    )
+    .await?;
+
+    let content = Content::json(SearchPointsResponse {
+        result: query_response.result.points,
+        time: query_response.time,
+    })?;
+
+    Ok(CallToolResult::success(vec![content]))
+}
+
+/// Send a request to the path made of the given segments on the Qdrant server and parse the
+/// response.
+async fn send_request<T: DeserializeOwned>(
+    conn_config: &ConnectionConfig,
+    method: Method,
+    segments: &[&str],
+    query: &[(&str, &str)],
+    params: Option<&Value>,
+    action: &str,
+) -> Result<T, McpError> {
+    let url = build_url(&conn_config.base_url, segments, query)?;
+
+    let mut request = reqwest::Client::new().request(method, url);
+    if let Some(api_key) = &conn_config.api_key {
+        request = request.header("api-key", api_key);
+    }
+    if let Some(params) = params {
+        request = request.json(params);
+    }
+    let result = request.send().await;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let error_message = format!("Failed to {action}: {e}");
+
+            error!("{}", error_message);
+
+            return Err(McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                error_message,
+                None,
+            ));
+        }
+    };
+
+    let status = response.status();
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => {
+            let error_message = format!("Failed to {action}: {e}");
+
+            error!("{}", error_message);
+
+            return Err(McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                error_message,
+                None,
+            ));
+        }
+    };
+
+    if !status.is_success() {
+        // Qdrant reports errors as `{"status": {"error": "..."}, "time": ...}`
+        let detail = match serde_json::from_str::<QdrantErrorResponse>(&body) {
+            Ok(QdrantErrorResponse {
+                status: QdrantErrorStatus { error },
+            }) => error,
+            Err(_) => body,
+        };
+        let error_message = format!("Failed to {action}: {detail}");
+
+        error!("{}", error_message);
+
+        return Err(McpError::new(
+            ErrorCode::INTERNAL_ERROR,
+            error_message,
+            Some(json!({ "status": status.as_u16(), "error": detail })),
+        ));
+    }
+
+    serde_json::from_str::<T>(&body).map_err(|e| {
+        let error_message = format!("Failed to {action}: malformed response from Qdrant: {e}");
+
+        error!("{}", error_message);
+
+        McpError::new(
+            ErrorCode::INTERNAL_ERROR,
+            error_message,
+            Some(json!({ "response": body })),
+        )
+    })
+}
+
+/// Build the url of a Qdrant API path. Each segment is percent-encoded, so that values of the tool
+/// calls, e.g. collection or field names, cannot change the path.
+fn build_url(base_url: &str, segments: &[&str], query: &[(&str, &str)]) -> Result<Url, McpError> {
+    // `.` and `..` would be dropped from the path instead of being encoded
+    if let Some(segment) = segments
+        .iter()
+        .find(|segment| matches!(**segment, "" | "." | ".."))
+    {
+        let error_message = format!("Invalid path segment `{segment}`");
+        error!("{}", error_message);
+        return Err(McpError::new(
+            ErrorCode::INVALID_PARAMS,
+            error_message,
+            None,
+        ));
+    }
+
+    let mut url = Url::parse(base_url).map_err(|e| {
+        let error_message = format!("Invalid Qdrant base url `{base_url}`: {e}");
+        error!("{}", error_message);
+        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+    })?;
+    match url.path_segments_mut() {
+        Ok(mut path) => {
+            path.pop_if_empty().extend(segments);
+        }
+        Err(_) => {
+            let error_message = format!("Invalid Qdrant base url `{base_url}`");
+            error!("{}", error_message);
+            return Err(McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                error_message,
+                None,
+            ));
+        }
+    }
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+
+    Ok(url)
+}
+
+/// Response envelope of the Qdrant REST API
+#[derive(Debug, Deserialize)]
+struct QdrantResponse<T> {
+    result: T,
+    #[serde(default)]
+    time: f64,
+}
+
+/// Error envelope of the Qdrant REST API
+#[derive(Debug, Deserialize)]
+struct QdrantErrorResponse {
+    status: QdrantErrorStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantErrorStatus {
+    error: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryResult {
+    points: Vec<ScoredPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScrollResult {
+    points: Vec<Record>,
+    next_page_offset: Option<PointId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountResult {
+    count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateResult {
+    operation_id: Option<u64>,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionsResult {
+    collections: Vec<CollectionName>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionName {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionInfo {
+    status: String,
+    points_count: Option<u64>,
+    config: CollectionConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionConfig {
+    params: CollectionParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionParams {
+    #[serde(default)]
+    vectors: Option<VectorsConfig>,
+    #[serde(default)]
+    sparse_vectors: Option<BTreeMap<String, Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum VectorsConfig {
+    Unnamed(VectorParams),
+    Named(BTreeMap<String, VectorParams>),
+}
+
+#[derive(Debug, Deserialize)]
+struct VectorParams {
+    size: u64,
+    distance: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, http::StatusCode};
+
+    /// Start a mock Qdrant server answering every request with the given status and body.
+    async fn mock_qdrant(status: StatusCode, body: impl ToString) -> ConnectionConfig {
+        let body = body.to_string();
+        let app = Router::new().fallback(move || {
+            let body = body.clone();
+            async move { (status, body) }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        ConnectionConfig {
+            base_url: format!("http://{addr}"),
+            api_key: None,
+            collection: Some("test".to_string()),
+            allowed_collections: vec![],
+            limit: 10,
+            max_limit: 100,
+            score_threshold: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn query_points_without_vectors_and_time() {
+        let conn_config = mock_qdrant(
+            StatusCode::OK,
+            json!({ "result": { "points": [{ "id": 1, "score": 0.9 }] } }),
+        )
+        .await;
+
+        let result = query_points(&conn_config, "test", &json!({}))
+            .await
+            .unwrap();
+        let response = SearchPointsResponse::from(result);
+
+        assert_eq!(response.result.len(), 1);
+        assert_eq!(response.result[0].id, Some(PointId::Num(1)));
+        assert!(response.result[0].payload.is_empty());
+        assert!(response.result[0].vector.is_none());
+        assert_eq!(response.time, 0.0);
+    }
+
+    #[tokio::test]
+    async fn query_points_with_named_vectors() {
+        let conn_config = mock_qdrant(
+            StatusCode::OK,
+            json!({
+                "result": {
+                    "points": [{
+                        "id": "5c56c793-69f3-4fbf-87e6-c4bf54c28c26",
+                        "score": 0.5,
+                        "payload": { "title": "hello" },
+                        "vector": {
+                            "dense": [0.1, 0.2],
+                            "sparse": { "indices": [3], "values": [1.0] }
+                        }
+                    }]
+                },
+                "time": 0.01
+            }),
+        )
+        .await;
+
+        let result = query_points(&conn_config, "test", &json!({}))
+            .await
+            .unwrap();
+        let response = SearchPointsResponse::from(result);
+
+        let point = &response.result[0];
+        assert_eq!(point.payload["title"], "hello");
+        match &point.vector {
+            Some(Vectors::Named(vectors)) => {
+                assert!(matches!(vectors["dense"], Vector::Dense(_)));
+                assert!(matches!(vectors["sparse"], Vector::Sparse(_)));
+            }
+            other => panic!("unexpected vectors: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn error_detail_is_returned() {
+        let conn_config = mock_qdrant(
+            StatusCode::BAD_REQUEST,
+            json!({
+                "status": { "error": "Wrong input: Vector dimension error: expected dim: 4, got 3" },
+                "time": 0.0
+            }),
+        )
+        .await;
+
+        let error = query_points(&conn_config, "test", &json!({}))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(
+            error.message,
+            "Failed to search points: Wrong input: Vector dimension error: expected dim: 4, got 3"
+        );
+        assert_eq!(error.data.unwrap()["status"], 400);
+    }
+
+    #[tokio::test]
+    async fn non_json_error_body_is_returned() {
+        let conn_config = mock_qdrant(StatusCode::BAD_GATEWAY, "upstream unavailable").await;
+
+        let error = query_points(&conn_config, "test", &json!({}))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Failed to search points: upstream unavailable"
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_response_is_an_error() {
+        let conn_config = mock_qdrant(
+            StatusCode::OK,
+            json!({ "result": { "points": [{ "id": 1 }] } }),
+        )
+        .await;
+
+        let error = query_points(&conn_config, "test", &json!({}))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert!(error.message.contains("missing field `score`"));
+    }
+
+    #[test]
+    fn build_url_encodes_path_segments() {
+        let url = build_url(
+            "http://127.0.0.1:6333/",
+            &["collections", "docs", "index", "../../secret"],
+            &[("wait", "true")],
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://127.0.0.1:6333/collections/docs/index/..%2F..%2Fsecret?wait=true"
+        );
+
+        // base url with a path, e.g. behind a reverse proxy
+        let url = build_url(
+            "https://example.com/qdrant",
+            &["collections", "a b?#%"],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/qdrant/collections/a%20b%3F%23%25"
+        );
+
+        for segment in ["..", ".", ""] {
+            let error = build_url(
+                "http://127.0.0.1:6333",
+                &["collections", "docs", "index", segment],
+                &[],
+            )
+            .unwrap_err();
+            assert_eq!(error.code, ErrorCode::INVALID_PARAMS, "{segment}");
+        }
+    }
+
+    #[tokio::test]
+    async fn path_traversal_does_not_reach_another_collection() {
+        // answer with the path of the request
+        let app = Router::new().fallback(|uri: axum::http::Uri| async move {
+            json!({ "result": uri.path(), "time": 0.0 }).to_string()
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let conn_config = ConnectionConfig {
+            base_url: format!("http://{addr}"),
+            ..mock_qdrant(StatusCode::OK, "").await
+        };
+
+        let response: QdrantResponse<String> = send_request(
+            &conn_config,
+            Method::DELETE,
+            &["collections", "test", "index", "../../secret"],
+            &[("wait", "true")],
+            None,
+            "delete payload index",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.result, "/collections/test/index/..%2F..%2Fsecret");
+
+        let error = send_request::<QdrantResponse<String>>(
+            &conn_config,
+            Method::DELETE,
+            &["collections", "test", "index", ".."],
+            &[],
+            None,
+            "delete payload index",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn admin_tools_are_opt_in() {
+        let names = |server: QdrantServer| -> Vec<String> {
+            server
+                .tool_router
+                .list_all()
+                .into_iter()
+                .map(|tool| tool.name.to_string())
+                .collect()
+        };
+
+        let tools = names(QdrantServer::new(false));
+        assert!(tools.contains(&"search".to_string()));
+        for name in ADMIN_TOOLS {
+            assert!(!tools.contains(&name.to_string()), "{name}");
+        }
+
+        let tools = names(QdrantServer::new(true));
+        for name in ADMIN_TOOLS {
+            assert!(tools.contains(&name.to_string()), "{name}");
+        }
+    }
+}
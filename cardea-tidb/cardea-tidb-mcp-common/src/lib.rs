@@ -1,6 +1,12 @@
+mod connection;
+mod pool;
+
+pub use connection::*;
 use mysql_common::prelude::FromRow;
+pub use pool::*;
 use rmcp::{model::CallToolResult, schemars};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct TidbSearchRequest {
@@ -33,3 +39,356 @@ pub struct TidbSearchHit {
     #[schemars(description = "The content of the tidb server")]
     pub content: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbVectorSearchRequest {
+    #[schemars(
+        description = "The query to search for, embedded by the embedding service. Must not be set together with `vector`"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[schemars(
+        description = "The query vector, which must have the same dimension as the vector column"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f64>>,
+    #[schemars(
+        description = "The maximum number of hits to return, greater than 0. Defaults to the limit configured on the server, and is capped at the maximum configured on the server"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbVectorSearchResponse {
+    #[schemars(description = "The hits, nearest first")]
+    pub hits: Vec<TidbVectorSearchHit>,
+}
+impl From<CallToolResult> for TidbVectorSearchResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbVectorSearchResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, FromRow)]
+pub struct TidbVectorSearchHit {
+    #[schemars(description = "The id of the row")]
+    pub id: i32,
+    #[schemars(description = "The title of the row")]
+    pub title: String,
+    #[schemars(description = "The content of the row")]
+    pub content: String,
+    #[schemars(description = "The cosine distance between the row and the query vector")]
+    pub distance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbHybridSearchRequest {
+    #[schemars(
+        description = "The query to search for. It is matched by the full-text search and embedded by the embedding service for the vector search"
+    )]
+    pub query: String,
+    #[schemars(
+        description = "The maximum number of hits to return, greater than 0. Defaults to the limit configured on the server, and is capped at the maximum configured on the server"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[schemars(
+        description = "The rank constant of the reciprocal rank fusion. Higher values give lower ranked rows more influence. Defaults to 60"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank_constant: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbHybridSearchResponse {
+    #[schemars(description = "The hits, best first")]
+    pub hits: Vec<TidbHybridSearchHit>,
+}
+impl From<CallToolResult> for TidbHybridSearchResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbHybridSearchResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, FromRow)]
+pub struct TidbHybridSearchHit {
+    #[schemars(description = "The id of the row")]
+    pub id: i32,
+    #[schemars(description = "The title of the row")]
+    pub title: String,
+    #[schemars(description = "The content of the row")]
+    pub content: String,
+    #[schemars(
+        description = "The reciprocal rank fusion score of the row over the full-text and vector rankings"
+    )]
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbQueryRequest {
+    #[schemars(
+        description = "A single read-only SQL query, e.g. `SELECT title FROM docs WHERE id < 10`. Statements that modify data or schema are rejected"
+    )]
+    pub sql: String,
+    #[schemars(
+        description = "The maximum number of rows to return. Defaults to, and is capped at, the maximum configured on the server"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbQueryResponse {
+    #[schemars(description = "The columns of the result set")]
+    pub columns: Vec<TidbQueryColumn>,
+    #[schemars(
+        description = "The rows of the result set, with one value per column. Numbers are returned as numbers, other values as strings"
+    )]
+    pub rows: Vec<Vec<Value>>,
+    #[schemars(description = "Whether the query returned more rows than `max_rows`")]
+    pub truncated: bool,
+}
+impl From<CallToolResult> for TidbQueryResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbQueryResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbQueryColumn {
+    #[schemars(description = "The name of the column")]
+    pub name: String,
+    #[schemars(
+        description = "The MySQL protocol type of the column, e.g. `long`, `double`, `newdecimal`, `var_string` or `datetime`"
+    )]
+    pub column_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbListTablesResponse {
+    #[schemars(description = "The tables and views of the database")]
+    pub tables: Vec<TidbTableInfo>,
+}
+impl From<CallToolResult> for TidbListTablesResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbListTablesResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbTableInfo {
+    #[schemars(description = "The name of the table")]
+    pub name: String,
+    #[schemars(description = "The type of the table, e.g. `BASE TABLE` or `VIEW`")]
+    pub table_type: String,
+    #[schemars(description = "The comment of the table")]
+    pub comment: String,
+    #[schemars(description = "The estimated number of rows of the table, if known")]
+    pub estimated_rows: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbDescribeTableRequest {
+    #[schemars(description = "The name of the table to describe")]
+    pub table: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbDescribeTableResponse {
+    #[schemars(description = "The name of the table")]
+    pub name: String,
+    #[schemars(description = "The comment of the table")]
+    pub comment: String,
+    #[schemars(description = "The columns of the table, in order")]
+    pub columns: Vec<TidbColumnInfo>,
+    #[schemars(description = "The indexes of the table, including full-text indexes")]
+    pub indexes: Vec<TidbIndexInfo>,
+}
+impl From<CallToolResult> for TidbDescribeTableResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbDescribeTableResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbColumnInfo {
+    #[schemars(description = "The name of the column")]
+    pub name: String,
+    #[schemars(description = "The SQL type of the column, e.g. `varchar(255)` or `vector(1536)`")]
+    pub column_type: String,
+    #[schemars(description = "Whether the column accepts NULL")]
+    pub nullable: bool,
+    #[schemars(description = "The default value of the column")]
+    pub default: Option<String>,
+    #[schemars(description = "Extra information, e.g. `auto_increment`")]
+    pub extra: String,
+    #[schemars(description = "The comment of the column")]
+    pub comment: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbIndexInfo {
+    #[schemars(description = "The name of the index, `PRIMARY` for the primary key")]
+    pub name: String,
+    #[schemars(description = "The indexed columns, in order")]
+    pub columns: Vec<String>,
+    #[schemars(description = "Whether the index is unique")]
+    pub unique: bool,
+    #[schemars(description = "Whether the index is a full-text index")]
+    pub fulltext: bool,
+    #[schemars(description = "The type of the index, e.g. `BTREE` or `FULLTEXT`")]
+    pub index_type: String,
+    #[schemars(description = "The comment of the index")]
+    pub comment: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbSampleRowsRequest {
+    #[schemars(description = "The name of the table to sample")]
+    pub table: String,
+    #[schemars(
+        description = "The number of rows to return. Defaults to 5 and is capped at the maximum number of rows of the query tool"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbInsertDocumentsRequest {
+    #[schemars(description = "The documents to insert")]
+    pub documents: Vec<TidbDocument>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbDocument {
+    #[schemars(
+        description = "The id of the document. A document with the id of an existing row replaces it. If not provided, an id is generated"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[schemars(description = "The title of the document")]
+    pub title: String,
+    #[schemars(description = "The content of the document, which is indexed for full-text search")]
+    pub content: String,
+    #[schemars(
+        description = "The vector of the document, stored in the vector column. If not provided and the table has a vector column, the content is embedded by the embedding service, if configured"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbInsertDocumentsResponse {
+    #[schemars(description = "The ids of the inserted documents, in the order of the request")]
+    pub ids: Vec<i32>,
+}
+impl From<CallToolResult> for TidbInsertDocumentsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbInsertDocumentsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbDeleteDocumentsRequest {
+    #[schemars(description = "The ids of the documents to delete")]
+    pub ids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbDeleteDocumentsResponse {
+    #[schemars(description = "The number of deleted documents")]
+    pub deleted: u64,
+}
+impl From<CallToolResult> for TidbDeleteDocumentsResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbDeleteDocumentsResponse>(&content).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbEnsureFulltextIndexRequest {
+    #[schemars(
+        description = "The parser of the full-text index, used when the index is created. Defaults to `standard`"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parser: Option<TidbFulltextParser>,
+    #[schemars(
+        description = "The dimension of the vector column, used when the table is created. If not provided, the table is created without a vector column"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector_dimensions: Option<u32>,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TidbFulltextParser {
+    #[default]
+    #[schemars(
+        description = "Splits words on whitespace and punctuation, for English and similar languages"
+    )]
+    Standard,
+    #[schemars(
+        description = "Tokenizes each language appropriately, for Chinese, Japanese, Korean and mixed-language text"
+    )]
+    Multilingual,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TidbEnsureFulltextIndexResponse {
+    #[schemars(description = "The name of the table")]
+    pub table: String,
+    #[schemars(description = "Whether the table was created")]
+    pub created_table: bool,
+    #[schemars(
+        description = "Whether the full-text index was created. If false, the table already had a full-text index on `content`"
+    )]
+    pub created_index: bool,
+}
+impl From<CallToolResult> for TidbEnsureFulltextIndexResponse {
+    fn from(result: CallToolResult) -> Self {
+        let content = match result.content.is_empty() {
+            false => result.content[0].as_text().unwrap().text.to_string(),
+            true => String::new(),
+        };
+
+        serde_json::from_str::<TidbEnsureFulltextIndexResponse>(&content).unwrap()
+    }
+}
@@ -0,0 +1,244 @@
+use mysql::{Pool, PooledConn, prelude::*};
+use rmcp::{ErrorData as McpError, model::ErrorCode};
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info, warn};
+
+/// Get a connection from the pool. The version and the table are only checked on the first
+/// connection after the database was unreachable, as recorded in `schema_checked`.
+pub fn get_conn(
+    pool: &Pool,
+    database: &str,
+    table_name: &str,
+    schema_checked: &AtomicBool,
+) -> Result<PooledConn, McpError> {
+    let mut conn = connect(pool, schema_checked)?;
+
+    ensure_schema_checked(schema_checked, &mut conn, |conn| {
+        check_schema(conn, database, table_name)
+    })?;
+
+    Ok(conn)
+}
+
+/// Get a connection from the pool without checking the schema. A failure is recorded in
+/// `schema_checked`, so that the schema is checked again once the database can be reached.
+pub fn connect(pool: &Pool, schema_checked: &AtomicBool) -> Result<PooledConn, McpError> {
+    debug!("Getting connection to TiDB...");
+    pool.get_conn().map_err(|e| {
+        schema_checked.store(false, Ordering::Release);
+
+        let error_message = format!("Failed to get connection: {e}");
+
+        error!(error_message);
+
+        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+    })
+}
+
+/// Check that the connection works and that the table exists.
+pub fn check_schema(
+    conn: &mut PooledConn,
+    database: &str,
+    table_name: &str,
+) -> Result<(), McpError> {
+    // test connection
+    debug!("Testing connection...");
+    let version: String = match conn.query_first("SELECT VERSION()").map_err(|e| {
+        let error_message = format!("Failed to query version: {e}");
+
+        error!(error_message);
+
+        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+    })? {
+        Some(version) => version,
+        None => {
+            let error_message = "Failed to query version";
+
+            error!(error_message);
+
+            return Err(McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                error_message,
+                None,
+            ));
+        }
+    };
+    debug!("Connected to TiDB! Version: {}", version);
+
+    // check if table exists
+    debug!("Checking if table exists...");
+    let table_exists: i32 = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM information_schema.tables
+                WHERE table_schema = ? AND table_name = ?",
+            (database, table_name),
+        )
+        .map_err(|e| {
+            let error_message = format!("Failed to check table: {e}");
+
+            error!(error_message);
+
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?
+        .unwrap_or(0);
+
+    if table_exists == 0 {
+        let error_message = format!("Not found table `{table_name}` in database `{database}`");
+
+        error!(error_message);
+
+        return Err(McpError::new(
+            ErrorCode::INTERNAL_ERROR,
+            error_message,
+            None,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Ping the database every `interval`. When it cannot be reached, the schema is checked again once
+/// it can, either here or by the next [`get_conn`].
+pub async fn health_check(
+    pool: Pool,
+    database: String,
+    table_name: String,
+    schema_checked: Arc<AtomicBool>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // the first tick completes immediately, and the schema was just checked at startup
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        let pool = pool.clone();
+        let database = database.clone();
+        let table_name = table_name.clone();
+        let schema_checked = schema_checked.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let ping = pool
+                .get_conn()
+                .and_then(|mut conn| conn.as_mut().ping().map(|_| conn));
+            record_ping(&schema_checked, ping, |conn| {
+                check_schema(conn, &database, &table_name)
+            });
+        })
+        .await;
+
+        if let Err(e) = result {
+            error!("TiDB health check task failed: {e}");
+        }
+    }
+}
+
+/// Run `check` on the connection unless the schema was checked since the database was last
+/// unreachable, and record its success.
+fn ensure_schema_checked<C>(
+    schema_checked: &AtomicBool,
+    conn: &mut C,
+    check: impl FnOnce(&mut C) -> Result<(), McpError>,
+) -> Result<(), McpError> {
+    if !schema_checked.load(Ordering::Acquire) {
+        check(conn)?;
+        schema_checked.store(true, Ordering::Release);
+    }
+
+    Ok(())
+}
+
+/// Record the result of a health check ping: the schema is checked again after a reconnection,
+/// and must be checked again after a failure.
+fn record_ping<C, E: fmt::Display>(
+    schema_checked: &AtomicBool,
+    ping: Result<C, E>,
+    check: impl FnOnce(&mut C) -> Result<(), McpError>,
+) {
+    match ping {
+        Ok(mut conn) => {
+            if !schema_checked.load(Ordering::Acquire) {
+                info!("TiDB is reachable, checking the schema again...");
+            }
+            // a failed check is logged, and the next tool call checks again
+            let _ = ensure_schema_checked(schema_checked, &mut conn, check);
+        }
+        Err(e) => {
+            if schema_checked.swap(false, Ordering::AcqRel) {
+                warn!("Health check failed, TiDB is unreachable: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn missing_table(_: &mut ()) -> Result<(), McpError> {
+        Err(McpError::new(
+            ErrorCode::INTERNAL_ERROR,
+            "Not found table",
+            None,
+        ))
+    }
+
+    #[test]
+    fn schema_is_checked_once_per_reconnection() {
+        let schema_checked = AtomicBool::new(false);
+        let checks = Cell::new(0);
+        let check = |_: &mut ()| {
+            checks.set(checks.get() + 1);
+            Ok(())
+        };
+
+        // checked on the first connection, not on the next ones
+        ensure_schema_checked(&schema_checked, &mut (), check).unwrap();
+        ensure_schema_checked(&schema_checked, &mut (), check).unwrap();
+        assert_eq!(checks.get(), 1);
+
+        // a failed check is retried by the next connection
+        schema_checked.store(false, Ordering::Release);
+        assert!(ensure_schema_checked(&schema_checked, &mut (), missing_table).is_err());
+        assert!(!schema_checked.load(Ordering::Acquire));
+        ensure_schema_checked(&schema_checked, &mut (), check).unwrap();
+        assert_eq!(checks.get(), 2);
+        assert!(schema_checked.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn health_check_rechecks_after_outage() {
+        let schema_checked = AtomicBool::new(true);
+        let checks = Cell::new(0);
+        let check = |_: &mut ()| {
+            checks.set(checks.get() + 1);
+            Ok(())
+        };
+
+        // a healthy database is not checked again
+        record_ping(&schema_checked, Ok::<_, String>(()), check);
+        assert_eq!(checks.get(), 0);
+
+        // an outage invalidates the check
+        record_ping(&schema_checked, Err("connection refused"), check);
+        assert!(!schema_checked.load(Ordering::Acquire));
+
+        // the schema is still broken after the reconnection, so it is checked again later
+        record_ping(&schema_checked, Ok::<_, String>(()), missing_table);
+        assert!(!schema_checked.load(Ordering::Acquire));
+
+        record_ping(&schema_checked, Ok::<_, String>(()), check);
+        assert_eq!(checks.get(), 1);
+        assert!(schema_checked.load(Ordering::Acquire));
+    }
+}
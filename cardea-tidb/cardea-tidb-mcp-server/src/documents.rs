@@ -0,0 +1,309 @@
+use crate::schema::{query_error, quote_identifier};
+use cardea_tidb_mcp_common::{TidbEnsureFulltextIndexResponse, TidbFulltextParser};
+use mysql::{PooledConn, TxOpts, Value, prelude::*};
+use rmcp::{ErrorData as McpError, model::ErrorCode};
+use tracing::{error, info};
+
+/// Id, title, content and vector literal of a document to insert
+pub type DocumentRow = (Option<i32>, String, String, Option<String>);
+
+/// Create the table with a full-text index on `content` if it does not exist, or add the index if
+/// the table has none.
+pub fn ensure_fulltext_index(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    vector_column: &str,
+    parser: TidbFulltextParser,
+    vector_dimensions: Option<u32>,
+) -> Result<TidbEnsureFulltextIndexResponse, McpError> {
+    let table_exists: Option<String> = conn
+        .exec_first(
+            "SELECT table_name FROM information_schema.tables
+                WHERE table_schema = ? AND table_name = ?",
+            (database, table),
+        )
+        .map_err(|e| query_error("check table", e))?;
+
+    if table_exists.is_none() {
+        let sql = create_table_sql(table, vector_column, parser, vector_dimensions);
+        info!("Creating table: {}", sql);
+        conn.query_drop(&sql)
+            .map_err(|e| write_error("create table", e))?;
+
+        return Ok(TidbEnsureFulltextIndexResponse {
+            table: table.to_string(),
+            created_table: true,
+            created_index: true,
+        });
+    }
+
+    let index: Option<String> = conn
+        .exec_first(
+            "SELECT index_name FROM information_schema.statistics
+                WHERE table_schema = ? AND table_name = ?
+                    AND column_name = 'content' AND index_type = 'FULLTEXT'",
+            (database, table),
+        )
+        .map_err(|e| query_error("check full-text index", e))?;
+
+    let created_index = match index {
+        Some(index) => {
+            info!(
+                "Table `{}` already has the full-text index `{}`",
+                table, index
+            );
+            false
+        }
+        None => {
+            let sql = add_fulltext_index_sql(table, parser);
+            info!("Creating full-text index: {}", sql);
+            conn.query_drop(&sql)
+                .map_err(|e| write_error("create full-text index", e))?;
+            true
+        }
+    };
+
+    Ok(TidbEnsureFulltextIndexResponse {
+        table: table.to_string(),
+        created_table: false,
+        created_index,
+    })
+}
+
+/// Check whether a table of the database has a column.
+pub fn has_column(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    column: &str,
+) -> Result<bool, McpError> {
+    let count: Option<u64> = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM information_schema.columns
+                WHERE table_schema = ? AND table_name = ? AND column_name = ?",
+            (database, table, column),
+        )
+        .map_err(|e| query_error("check column", e))?;
+
+    Ok(count.unwrap_or(0) > 0)
+}
+
+/// Insert the documents in a single transaction, replacing the rows with the same ids. The vectors
+/// are written to `vector_column` if set. Returns the ids of the documents, generated ones
+/// included.
+pub fn insert_documents(
+    conn: &mut PooledConn,
+    table: &str,
+    vector_column: Option<&str>,
+    documents: Vec<DocumentRow>,
+) -> Result<Vec<i32>, McpError> {
+    info!(
+        "Inserting {} documents into `{}`...",
+        documents.len(),
+        table
+    );
+    let sql = insert_sql(table, vector_column);
+
+    let mut tx = conn
+        .start_transaction(TxOpts::default())
+        .map_err(|e| write_error("insert documents", e))?;
+
+    let mut ids = Vec::with_capacity(documents.len());
+    for (id, title, content, vector) in documents {
+        let mut params: Vec<Value> = vec![id.into(), title.into(), content.into()];
+        if vector_column.is_some() {
+            params.push(vector.into());
+        }
+        tx.exec_drop(&sql, params)
+            .map_err(|e| write_error("insert documents", e))?;
+
+        let id = match id {
+            Some(id) => id,
+            None => tx
+                .last_insert_id()
+                .and_then(|id| i32::try_from(id).ok())
+                .ok_or_else(|| {
+                    let error_message = "Failed to read the generated id of a document";
+                    error!(error_message);
+                    McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+                })?,
+        };
+        ids.push(id);
+    }
+
+    tx.commit()
+        .map_err(|e| write_error("insert documents", e))?;
+
+    Ok(ids)
+}
+
+/// Delete the documents with the given ids. Returns the number of deleted rows.
+pub fn delete_documents(conn: &mut PooledConn, table: &str, ids: &[i32]) -> Result<u64, McpError> {
+    info!("Deleting {} documents from `{}`...", ids.len(), table);
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let sql = format!(
+        "DELETE FROM {} WHERE id IN ({placeholders})",
+        quote_identifier(table)
+    );
+
+    conn.exec_drop(&sql, ids.to_vec())
+        .map_err(|e| write_error("delete documents", e))?;
+
+    Ok(conn.affected_rows())
+}
+
+fn create_table_sql(
+    table: &str,
+    vector_column: &str,
+    parser: TidbFulltextParser,
+    vector_dimensions: Option<u32>,
+) -> String {
+    let vector_column = match vector_dimensions {
+        Some(dimensions) => format!(
+            "\n    {} VECTOR({dimensions}),",
+            quote_identifier(vector_column)
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        "CREATE TABLE {} (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,{vector_column}
+    FULLTEXT INDEX (content) WITH PARSER {}
+)",
+        quote_identifier(table),
+        parser_name(parser)
+    )
+}
+
+/// The full-text index is served by TiFlash, so a columnar replica is added if the table has none.
+fn add_fulltext_index_sql(table: &str, parser: TidbFulltextParser) -> String {
+    format!(
+        "ALTER TABLE {} ADD FULLTEXT INDEX (content) WITH PARSER {} ADD_COLUMNAR_REPLICA_ON_DEMAND",
+        quote_identifier(table),
+        parser_name(parser)
+    )
+}
+
+fn insert_sql(table: &str, vector_column: Option<&str>) -> String {
+    let mut columns = vec!["id".to_string(), "title".to_string(), "content".to_string()];
+    if let Some(vector_column) = vector_column {
+        columns.push(quote_identifier(vector_column));
+    }
+
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let updates: Vec<String> = columns[1..]
+        .iter()
+        .map(|column| format!("{column} = VALUES({column})"))
+        .collect();
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({placeholders}) ON DUPLICATE KEY UPDATE {}",
+        quote_identifier(table),
+        columns.join(", "),
+        updates.join(", ")
+    )
+}
+
+fn parser_name(parser: TidbFulltextParser) -> &'static str {
+    match parser {
+        TidbFulltextParser::Standard => "STANDARD",
+        TidbFulltextParser::Multilingual => "MULTILINGUAL",
+    }
+}
+
+/// MySQL error codes caused by the documents themselves: a duplicate key, a NULL, out of range or
+/// too long value, a value of the wrong type, or a violated foreign key or check constraint.
+const DOCUMENT_ERROR_CODES: [u16; 10] =
+    [1048, 1062, 1264, 1265, 1292, 1366, 1406, 1451, 1452, 3819];
+
+/// Errors caused by the documents are invalid params. Others, e.g. a lost connection or a failed
+/// commit, are internal errors.
+fn write_error(action: &str, e: mysql::Error) -> McpError {
+    let error_message = format!("Failed to {action}: {e}");
+    error!(error_message);
+
+    let code = match &e {
+        mysql::Error::MySqlError(e) if DOCUMENT_ERROR_CODES.contains(&e.code) => {
+            ErrorCode::INVALID_PARAMS
+        }
+        _ => ErrorCode::INTERNAL_ERROR,
+    };
+    McpError::new(code, error_message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_write_sql() {
+        assert_eq!(
+            create_table_sql(
+                "docs",
+                "embedding",
+                TidbFulltextParser::Multilingual,
+                Some(768)
+            ),
+            "CREATE TABLE `docs` (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    `embedding` VECTOR(768),
+    FULLTEXT INDEX (content) WITH PARSER MULTILINGUAL
+)"
+        );
+        assert!(
+            !create_table_sql("docs", "embedding", TidbFulltextParser::Standard, None)
+                .contains("VECTOR")
+        );
+        assert_eq!(
+            add_fulltext_index_sql("docs", TidbFulltextParser::Standard),
+            "ALTER TABLE `docs` ADD FULLTEXT INDEX (content) WITH PARSER STANDARD ADD_COLUMNAR_REPLICA_ON_DEMAND"
+        );
+        assert_eq!(
+            insert_sql("docs", Some("embedding")),
+            "INSERT INTO `docs` (id, title, content, `embedding`) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE title = VALUES(title), content = VALUES(content), `embedding` = VALUES(`embedding`)"
+        );
+        assert_eq!(
+            insert_sql("docs", None),
+            "INSERT INTO `docs` (id, title, content) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE title = VALUES(title), content = VALUES(content)"
+        );
+    }
+
+    #[test]
+    fn only_document_errors_are_invalid_params() {
+        let mysql_error = |code| {
+            mysql::Error::MySqlError(mysql::MySqlError {
+                state: "23000".to_string(),
+                message: "error".to_string(),
+                code,
+            })
+        };
+
+        // duplicate key
+        assert_eq!(
+            write_error("insert documents", mysql_error(1062)).code,
+            ErrorCode::INVALID_PARAMS
+        );
+        // data too long
+        assert_eq!(
+            write_error("insert documents", mysql_error(1406)).code,
+            ErrorCode::INVALID_PARAMS
+        );
+        // table does not exist
+        assert_eq!(
+            write_error("insert documents", mysql_error(1146)).code,
+            ErrorCode::INTERNAL_ERROR
+        );
+        // lost connection
+        let io_error = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert_eq!(
+            write_error("insert documents", mysql::Error::IoError(io_error)).code,
+            ErrorCode::INTERNAL_ERROR
+        );
+    }
+}
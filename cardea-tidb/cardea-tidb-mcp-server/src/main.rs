@@ -1,22 +1,32 @@
+mod documents;
+mod query;
+mod schema;
 mod tidb;
 
 use anyhow::{anyhow, bail};
+use cardea_tidb_mcp_common::{SslMode, TidbConnectionUrl};
 use clap::{Parser, ValueEnum};
 use mysql::*;
 use once_cell::sync::OnceCell;
-use regex::Regex;
 use rmcp::transport::{
     sse_server::SseServer,
     streamable_http_server::{StreamableHttpService, session::local::LocalSessionManager},
 };
 use rustls::crypto::{CryptoProvider, ring::default_provider};
-use std::{env, path::PathBuf};
+use std::{
+    env,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 use tidb::TidbServer;
 use tokio::sync::RwLock as TokioRwLock;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::{self, layer::SubscriberExt, util::SubscriberInitExt};
 
-pub static SSL_CA_PATH: OnceCell<PathBuf> = OnceCell::new();
 pub static TIDB_ACCESS_CONFIG: OnceCell<TokioRwLock<TidbAccessConfig>> = OnceCell::new();
 
 const DEFAULT_SOCKET_ADDR: &str = "127.0.0.1:8007";
@@ -26,9 +36,9 @@ const DEFAULT_SOCKET_ADDR: &str = "127.0.0.1:8007";
 struct Args {
     /// Path to the SSL CA certificate. On macOS, this is typically
     /// `/etc/ssl/cert.pem`. On Debian/Ubuntu/Arch Linux, it's typically
-    /// `/etc/ssl/certs/ca-certificates.crt`.
-    #[arg(long, required = true)]
-    ssl_ca: PathBuf,
+    /// `/etc/ssl/certs/ca-certificates.crt`. If not set, the built-in root certificates are used
+    #[arg(long)]
+    ssl_ca: Option<PathBuf>,
     /// Socket address to bind to
     #[arg(short, long, default_value = DEFAULT_SOCKET_ADDR)]
     socket_addr: String,
@@ -38,9 +48,31 @@ struct Args {
     /// Table name
     #[arg(long, required = true)]
     table_name: String,
-    /// Maximum number of query results to return
+    /// Maximum number of query results to return when a search does not specify `limit`
     #[arg(long, default_value = "10")]
     limit: u64,
+    /// Upper bound for the `limit` of the vector and hybrid search
+    #[arg(long, default_value = "100")]
+    max_limit: u64,
+    /// The base URL of the embedding server used by the vector and hybrid search, e.g., "https://api.openai.com/v1"
+    #[arg(long)]
+    embedding_service: Option<String>,
+    /// Name of the VECTOR column used by the vector and hybrid search
+    #[arg(long, default_value = "embedding")]
+    vector_column: String,
+    /// Maximum number of rows returned by the query tool
+    #[arg(long, default_value = "100")]
+    query_max_rows: u64,
+    /// Maximum execution time of the query tool, in seconds
+    #[arg(long, default_value = "10")]
+    query_timeout: u64,
+    /// Interval in seconds between the health checks of the TiDB connection. 0 disables them
+    #[arg(long, default_value = "30")]
+    health_check_interval: u64,
+    /// Enable the tools that write to the database: `insert_documents`, `delete_documents` and
+    /// `ensure_fulltext_index`. The table given by `--table-name` may then be missing at startup
+    #[arg(long)]
+    enable_write_tools: bool,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -67,38 +99,47 @@ async fn main() -> anyhow::Result<()> {
 
     let args = Args::parse();
 
+    if args.query_max_rows == 0 || args.query_timeout == 0 {
+        bail!("--query-max-rows and --query-timeout must be greater than 0");
+    }
+    if args.limit == 0 || args.limit > args.max_limit {
+        bail!(
+            "--limit must be between 1 and --max-limit ({})",
+            args.max_limit
+        );
+    }
+
     // parse connection string
-    let (username, password, host, port, database) = match env::var("TIDB_CONNECTION") {
-        Ok(ref conn) => {
-            parse_tidb_conn_str(conn.as_str()).ok_or_else(|| anyhow!(
-                "Invalid connection string! The pattern should be `mysql://<USERNAME>:<PASSWORD>@<HOST>:<PORT>/<DATABASE>`"
-            ))?
-        }
+    let url = match env::var("TIDB_CONNECTION") {
+        Ok(conn) => conn.parse::<TidbConnectionUrl>().map_err(|e| {
+            let error_message = format!(
+                "Invalid TIDB_CONNECTION: {e}. The pattern should be `mysql://<USERNAME>:<PASSWORD>@<HOST>[:<PORT>]/<DATABASE>[?ssl-mode=<MODE>]`"
+            );
+            error!(error_message);
+            anyhow!(error_message)
+        })?,
         Err(e) => {
             let error_message = format!("Failed to get TIDB_CONNECTION: {e}");
             error!(error_message);
             bail!(error_message);
         }
     };
+    let database = url.database.clone();
 
-    // convert port to u16
-    let port = port.parse::<u16>().map_err(|e| {
-        let error_message = format!("Failed to parse TIDB_PORT: {e}");
-        error!(error_message);
-        anyhow!(error_message)
-    })?;
+    info!("TLS mode: {:?}", url.ssl_mode);
+    if url.ssl_mode == SslMode::Disabled && args.ssl_ca.is_some() {
+        warn!("TLS is disabled, ignoring --ssl-ca");
+    }
 
     // create connection options
-    info!("Creating connection options for TiDB Cloud...");
+    info!("Creating connection options for TiDB...");
     let opts = OptsBuilder::new()
-        .ip_or_hostname(Some(host))
-        .tcp_port(port)
-        .user(Some(username))
-        .pass(Some(password))
-        .db_name(Some(database.clone()))
-        .ssl_opts(Some(
-            SslOpts::default().with_root_cert_path(Some(args.ssl_ca)),
-        ));
+        .ip_or_hostname(Some(url.host))
+        .tcp_port(url.port)
+        .user(Some(url.username))
+        .pass(url.password)
+        .db_name(Some(url.database))
+        .ssl_opts(url.ssl_mode.ssl_opts(args.ssl_ca));
 
     // create connection pool
     info!("Creating connection pool...");
@@ -108,13 +149,50 @@ async fn main() -> anyhow::Result<()> {
         anyhow!(error_message)
     })?;
 
+    // check the schema once at startup, so that a missing table fails fast, unless the write
+    // tools can create it
+    let schema_checked = Arc::new(AtomicBool::new(false));
+    let mut conn = cardea_tidb_mcp_common::connect(&pool, &schema_checked)
+        .map_err(|e| anyhow!(e.message.to_string()))?;
+    match cardea_tidb_mcp_common::check_schema(&mut conn, &database, &args.table_name) {
+        Ok(()) => schema_checked.store(true, Ordering::Release),
+        Err(e) if args.enable_write_tools => warn!(
+            "{}. The searches fail until the table is created, e.g. with `ensure_fulltext_index`",
+            e.message
+        ),
+        Err(e) => bail!(e.message.to_string()),
+    }
+    drop(conn);
+
+    // parse embedding service api key
+    let embedding_service = args.embedding_service.map(|url| ServiceConfig {
+        url,
+        api_key: env::var("EMBEDDING_SERVICE_API_KEY").ok(),
+    });
+
     let config = TidbAccessConfig {
         pool,
         database,
         table_name: args.table_name,
         limit: args.limit,
+        max_limit: args.max_limit,
+        vector_column: args.vector_column,
+        embedding_service,
+        query_max_rows: args.query_max_rows,
+        query_timeout: args.query_timeout,
+        schema_checked,
     };
 
+    if args.health_check_interval > 0 {
+        tokio::spawn(cardea_tidb_mcp_common::health_check(
+            config.pool.clone(),
+            config.database.clone(),
+            config.table_name.clone(),
+            config.schema_checked.clone(),
+            Duration::from_secs(args.health_check_interval),
+        ));
+    }
+
     TIDB_ACCESS_CONFIG
         .set(TokioRwLock::new(config))
         .map_err(|_| anyhow::anyhow!("Failed to set TIDB_ACCESS_CONFIG"))?;
@@ -124,7 +202,7 @@ async fn main() -> anyhow::Result<()> {
     match args.transport {
         TransportType::StreamHttp => {
             let service = StreamableHttpService::new(
-                || Ok(TidbServer::new()),
+                move || Ok(TidbServer::new(args.enable_write_tools)),
                 LocalSessionManager::default().into(),
                 Default::default(),
             );
@@ -138,7 +216,7 @@ async fn main() -> anyhow::Result<()> {
         TransportType::Sse => {
             let ct = SseServer::serve(args.socket_addr.parse()?)
                 .await?
-                .with_service(TidbServer::new);
+                .with_service(move || TidbServer::new(args.enable_write_tools));
 
             tokio::signal::ctrl_c().await?;
             ct.cancel();
@@ -154,18 +232,49 @@ pub struct TidbAccessConfig {
     pub database: String,
     pub table_name: String,
     pub limit: u64,
+    /// upper bound for the limit of the vector and hybrid search
+    pub max_limit: u64,
+    /// name of the VECTOR column
+    pub vector_column: String,
+    /// embedding service used to embed the query of the vector and hybrid search
+    pub embedding_service: Option<ServiceConfig>,
+    /// maximum number of rows returned by the query tool
+    pub query_max_rows: u64,
+    /// maximum execution time of the query tool, in seconds
+    pub query_timeout: u64,
+    /// whether the version and the table were checked since the database was last unreachable
+    pub schema_checked: Arc<AtomicBool>,
 }
 
-fn parse_tidb_conn_str(conn_str: &str) -> Option<(String, String, String, String, String)> {
-    let re = Regex::new(r"^mysql://([^:]+):([^@]+)@([^:/]+):(\d+)/(.+)$").unwrap();
-    if let Some(caps) = re.captures(conn_str) {
-        let username = caps.get(1)?.as_str().to_string();
-        let password = caps.get(2)?.as_str().to_string();
-        let host = caps.get(3)?.as_str().to_string();
-        let port = caps.get(4)?.as_str().to_string();
-        let database = caps.get(5)?.as_str().to_string();
-        Some((username, password, host, port, database))
-    } else {
-        None
+#[derive(Clone)]
+pub struct ServiceConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+}
+
+impl std::fmt::Debug for ServiceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never print the api key
+        f.debug_struct("ServiceConfig")
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_hides_api_key() {
+        let service = ServiceConfig {
+            url: "https://api.openai.com/v1".to_string(),
+            api_key: Some("sk-secret".to_string()),
+        };
+
+        let debug = format!("{service:?}");
+        assert!(!debug.contains("secret"), "{debug}");
+        assert!(debug.contains("https://api.openai.com/v1"));
     }
 }
@@ -0,0 +1,266 @@
+use cardea_tidb_mcp_common::TidbQueryColumn;
+use mysql::{Value, consts::ColumnType};
+use rmcp::{ErrorData as McpError, model::ErrorCode};
+use serde_json::Value as JsonValue;
+use sqlparser::{
+    ast::{Expr, LimitClause, Query, SetExpr, Statement, Value as SqlValue, Visit, Visitor},
+    dialect::MySqlDialect,
+    parser::Parser,
+};
+use std::ops::ControlFlow;
+use tracing::error;
+
+/// Check that the SQL text is a single read-only query, and limit it to at most `max_rows + 1` rows,
+/// so that truncated results can be detected without reading every row. Returns the SQL to
+/// execute.
+pub fn prepare_query(sql: &str, max_rows: u64) -> Result<String, McpError> {
+    let mut statements = Parser::parse_sql(&MySqlDialect {}, sql)
+        .map_err(|e| invalid_params(format!("Failed to parse the SQL statement: {e}")))?;
+
+    if statements.len() != 1 {
+        return Err(invalid_params(format!(
+            "Exactly one SQL statement is allowed, found {}",
+            statements.len()
+        )));
+    }
+
+    let mut query = match statements.remove(0) {
+        Statement::Query(query) => query,
+        statement => {
+            let keyword = statement.to_string();
+            let keyword = keyword.split_whitespace().next().unwrap_or_default();
+            return Err(invalid_params(format!(
+                "Only read-only queries (SELECT) are allowed, found `{keyword}`"
+            )));
+        }
+    };
+    if let ControlFlow::Break(error) = query.visit(&mut ReadOnlyCheck) {
+        return Err(error);
+    }
+
+    // a smaller limit is kept, a larger or non-numeric one is replaced
+    let cap = max_rows.saturating_add(1);
+    query.limit_clause = Some(match query.limit_clause.take() {
+        None => LimitClause::LimitOffset {
+            limit: Some(limit_value(cap)),
+            offset: None,
+            limit_by: vec![],
+        },
+        Some(LimitClause::LimitOffset {
+            limit,
+            offset,
+            limit_by,
+        }) => LimitClause::LimitOffset {
+            limit: Some(capped_limit(limit, cap)),
+            offset,
+            limit_by,
+        },
+        Some(LimitClause::OffsetCommaLimit { offset, limit }) => LimitClause::OffsetCommaLimit {
+            offset,
+            limit: capped_limit(Some(limit), cap),
+        },
+    });
+
+    Ok(query.to_string())
+}
+
+/// Keep `limit` if it is a number not greater than `cap`, or replace it with `cap`.
+fn capped_limit(limit: Option<Expr>, cap: u64) -> Expr {
+    let keep = |value: &SqlValue| match value {
+        SqlValue::Number(n, _) => n.parse::<u64>().is_ok_and(|n| n <= cap),
+        _ => false,
+    };
+
+    match limit {
+        Some(Expr::Value(value)) if keep(&value.value) => Expr::Value(value),
+        _ => limit_value(cap),
+    }
+}
+
+fn limit_value(limit: u64) -> Expr {
+    Expr::value(SqlValue::Number(limit.to_string(), false))
+}
+
+/// Visit every query of a statement, including subqueries and common table expressions, and
+/// reject the ones that could write or lock.
+struct ReadOnlyCheck;
+impl Visitor for ReadOnlyCheck {
+    type Break = McpError;
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        if !query.locks.is_empty() {
+            return ControlFlow::Break(invalid_params(
+                "Locking reads (`FOR UPDATE`, `FOR SHARE`) are not allowed".to_string(),
+            ));
+        }
+
+        match check_set_expr(&query.body) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(error) => ControlFlow::Break(error),
+        }
+    }
+}
+
+/// Check the body of a query. Nested queries are checked when they are visited.
+fn check_set_expr(body: &SetExpr) -> Result<(), McpError> {
+    match body {
+        SetExpr::Select(select) => match select.into {
+            Some(_) => Err(invalid_params(
+                "`SELECT ... INTO` is not allowed".to_string(),
+            )),
+            None => Ok(()),
+        },
+        SetExpr::SetOperation { left, right, .. } => {
+            check_set_expr(left)?;
+            check_set_expr(right)
+        }
+        SetExpr::Query(_) | SetExpr::Values(_) | SetExpr::Table(_) => Ok(()),
+        _ => Err(invalid_params(
+            "Only read-only queries (SELECT) are allowed".to_string(),
+        )),
+    }
+}
+
+/// Describe a column of a result set, e.g. `long` for an `INT` column.
+pub fn column(name: String, column_type: ColumnType) -> TidbQueryColumn {
+    let column_type = format!("{column_type:?}");
+    TidbQueryColumn {
+        name,
+        column_type: column_type
+            .trim_start_matches("MYSQL_TYPE_")
+            .to_ascii_lowercase(),
+    }
+}
+
+/// Convert a value of the binary protocol to JSON. Numbers are kept as numbers, temporal values
+/// are formatted as in MySQL, and `DECIMAL`, `JSON` and string values are returned as strings.
+pub fn to_json(value: Value, column_type: ColumnType) -> JsonValue {
+    match value {
+        Value::NULL => JsonValue::Null,
+        Value::Bytes(bytes) => JsonValue::String(String::from_utf8_lossy(&bytes).into_owned()),
+        Value::Int(value) => value.into(),
+        Value::UInt(value) => value.into(),
+        Value::Float(value) => value.into(),
+        Value::Double(value) => value.into(),
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            let date = format!("{year:04}-{month:02}-{day:02}");
+            match column_type {
+                ColumnType::MYSQL_TYPE_DATE => date.into(),
+                _ => format!(
+                    "{date} {hour:02}:{minute:02}:{second:02}{}",
+                    fraction(micros)
+                )
+                .into(),
+            }
+        }
+        Value::Time(negative, days, hours, minutes, seconds, micros) => format!(
+            "{}{:02}:{minutes:02}:{seconds:02}{}",
+            if negative { "-" } else { "" },
+            days * 24 + u32::from(hours),
+            fraction(micros)
+        )
+        .into(),
+    }
+}
+
+fn fraction(micros: u32) -> String {
+    match micros {
+        0 => String::new(),
+        micros => format!(".{micros:06}"),
+    }
+}
+
+fn invalid_params(error_message: String) -> McpError {
+    error!("{}", error_message);
+    McpError::new(ErrorCode::INVALID_PARAMS, error_message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepare_query_allows_only_reads() {
+        assert_eq!(
+            prepare_query("SELECT id, title FROM docs WHERE id > 1", 100).unwrap(),
+            "SELECT id, title FROM docs WHERE id > 1 LIMIT 101"
+        );
+        assert_eq!(
+            prepare_query(
+                "WITH t AS (SELECT id FROM docs) SELECT * FROM t LIMIT 5",
+                100
+            )
+            .unwrap(),
+            "WITH t AS (SELECT id FROM docs) SELECT * FROM t LIMIT 5"
+        );
+        assert_eq!(
+            prepare_query("SELECT id FROM docs LIMIT 1000000 OFFSET 10", 100).unwrap(),
+            "SELECT id FROM docs LIMIT 101 OFFSET 10"
+        );
+        assert_eq!(
+            prepare_query("SELECT id FROM docs LIMIT 10, 1000000", 100).unwrap(),
+            "SELECT id FROM docs LIMIT 10, 101"
+        );
+        assert_eq!(
+            prepare_query("SELECT id FROM docs LIMIT 10, 20", 100).unwrap(),
+            "SELECT id FROM docs LIMIT 10, 20"
+        );
+        assert_eq!(
+            prepare_query("SELECT id FROM docs UNION SELECT id FROM notes", 100).unwrap(),
+            "SELECT id FROM docs UNION SELECT id FROM notes LIMIT 101"
+        );
+
+        for sql in [
+            "DELETE FROM docs",
+            "UPDATE docs SET title = 'x'",
+            "DROP TABLE docs",
+            "SELECT 1; DROP TABLE docs",
+            "SELECT * FROM docs FOR UPDATE",
+            "SELECT * INTO OUTFILE '/tmp/docs' FROM docs",
+            "SELECT * FROM (SELECT id FROM docs FOR UPDATE) t",
+            "SELECT id FROM docs WHERE id IN (SELECT id FROM docs FOR SHARE)",
+            "WITH t AS (SELECT id FROM docs FOR UPDATE) SELECT * FROM t",
+            "SELEC 1",
+        ] {
+            let error = prepare_query(sql, 100).unwrap_err();
+            assert_eq!(error.code, ErrorCode::INVALID_PARAMS, "{sql}");
+        }
+    }
+
+    #[test]
+    fn to_json_keeps_types() {
+        assert_eq!(to_json(Value::Int(-3), ColumnType::MYSQL_TYPE_LONG), -3);
+        assert_eq!(
+            to_json(
+                Value::Bytes(b"1.50".to_vec()),
+                ColumnType::MYSQL_TYPE_NEWDECIMAL
+            ),
+            "1.50"
+        );
+        assert_eq!(
+            to_json(
+                Value::Date(2025, 6, 22, 0, 0, 0, 0),
+                ColumnType::MYSQL_TYPE_DATE
+            ),
+            "2025-06-22"
+        );
+        assert_eq!(
+            to_json(
+                Value::Date(2025, 6, 22, 8, 19, 22, 500),
+                ColumnType::MYSQL_TYPE_DATETIME
+            ),
+            "2025-06-22 08:19:22.000500"
+        );
+        assert_eq!(
+            to_json(
+                Value::Time(true, 1, 2, 3, 4, 0),
+                ColumnType::MYSQL_TYPE_TIME
+            ),
+            "-26:03:04"
+        );
+        assert_eq!(
+            to_json(Value::NULL, ColumnType::MYSQL_TYPE_NULL),
+            JsonValue::Null
+        );
+    }
+}
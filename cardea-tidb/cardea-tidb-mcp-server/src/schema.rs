@@ -0,0 +1,193 @@
+use cardea_tidb_mcp_common::{
+    TidbColumnInfo, TidbDescribeTableResponse, TidbIndexInfo, TidbTableInfo,
+};
+use mysql::{PooledConn, prelude::*};
+use rmcp::{ErrorData as McpError, model::ErrorCode};
+use tracing::{error, info};
+
+/// Name, type, nullability, default, extra and comment of a column
+type ColumnRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    String,
+    Option<String>,
+);
+
+/// Index name, non-uniqueness, column name, index type and comment of an indexed column
+type IndexColumnRow = (String, i64, Option<String>, String, Option<String>);
+
+/// List the tables and views of the database.
+pub fn list_tables(conn: &mut PooledConn, database: &str) -> Result<Vec<TidbTableInfo>, McpError> {
+    info!("Listing tables of database `{}`...", database);
+    let rows: Vec<(String, String, Option<String>, Option<u64>)> = conn
+        .exec(
+            "SELECT table_name, table_type, table_comment, table_rows
+                FROM information_schema.tables
+                WHERE table_schema = ?
+                ORDER BY table_name",
+            (database,),
+        )
+        .map_err(|e| query_error("list tables", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(name, table_type, comment, estimated_rows)| TidbTableInfo {
+                name,
+                table_type,
+                comment: comment.unwrap_or_default(),
+                estimated_rows,
+            },
+        )
+        .collect())
+}
+
+/// Describe the columns and indexes of a table of the database.
+pub fn describe_table(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+) -> Result<TidbDescribeTableResponse, McpError> {
+    info!("Describing table `{}`...", table);
+    let comment: Option<Option<String>> = conn
+        .exec_first(
+            "SELECT table_comment FROM information_schema.tables
+                WHERE table_schema = ? AND table_name = ?",
+            (database, table),
+        )
+        .map_err(|e| query_error("describe table", e))?;
+    let comment = match comment {
+        Some(comment) => comment.unwrap_or_default(),
+        None => return Err(table_not_found(database, table)),
+    };
+
+    let columns: Vec<ColumnRow> = conn
+        .exec(
+            "SELECT column_name, column_type, is_nullable, column_default, extra, column_comment
+                FROM information_schema.columns
+                WHERE table_schema = ? AND table_name = ?
+                ORDER BY ordinal_position",
+            (database, table),
+        )
+        .map_err(|e| query_error("describe table", e))?;
+    let columns = columns
+        .into_iter()
+        .map(
+            |(name, column_type, nullable, default, extra, comment)| TidbColumnInfo {
+                name,
+                column_type,
+                nullable: nullable.eq_ignore_ascii_case("YES"),
+                default,
+                extra,
+                comment: comment.unwrap_or_default(),
+            },
+        )
+        .collect();
+
+    // one row per indexed column, ordered by index and position in the index
+    let index_columns: Vec<IndexColumnRow> = conn
+        .exec(
+            "SELECT index_name, non_unique, column_name, index_type, index_comment
+                FROM information_schema.statistics
+                WHERE table_schema = ? AND table_name = ?
+                ORDER BY index_name = 'PRIMARY' DESC, index_name, seq_in_index",
+            (database, table),
+        )
+        .map_err(|e| query_error("describe table", e))?;
+    let mut indexes: Vec<TidbIndexInfo> = Vec::new();
+    for (name, non_unique, column, index_type, comment) in index_columns {
+        if indexes.last().is_none_or(|index| index.name != name) {
+            indexes.push(TidbIndexInfo {
+                name,
+                columns: vec![],
+                unique: non_unique == 0,
+                fulltext: index_type.eq_ignore_ascii_case("FULLTEXT"),
+                index_type,
+                comment: comment.unwrap_or_default(),
+            });
+        }
+        // expression indexes have no column name
+        if let (Some(index), Some(column)) = (indexes.last_mut(), column) {
+            index.columns.push(column);
+        }
+    }
+
+    Ok(TidbDescribeTableResponse {
+        name: table.to_string(),
+        comment,
+        columns,
+        indexes,
+    })
+}
+
+/// Build the query returning the first `limit` rows of a table of the database. `VECTOR` columns
+/// are left out, as they are large and of little use to read.
+pub fn sample_rows_sql(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    limit: u64,
+) -> Result<String, McpError> {
+    let columns: Vec<(String, String)> = conn
+        .exec(
+            "SELECT column_name, data_type FROM information_schema.columns
+                WHERE table_schema = ? AND table_name = ?
+                ORDER BY ordinal_position",
+            (database, table),
+        )
+        .map_err(|e| query_error("sample rows", e))?;
+    if columns.is_empty() {
+        return Err(table_not_found(database, table));
+    }
+
+    let columns: Vec<String> = columns
+        .into_iter()
+        .filter(|(_, data_type)| !data_type.eq_ignore_ascii_case("vector"))
+        .map(|(name, _)| quote_identifier(&name))
+        .collect();
+    if columns.is_empty() {
+        let error_message = format!("Table `{table}` has no columns to sample");
+        error!(error_message);
+        return Err(McpError::new(
+            ErrorCode::INVALID_PARAMS,
+            error_message,
+            None,
+        ));
+    }
+
+    Ok(format!(
+        "SELECT {} FROM {} LIMIT {limit}",
+        columns.join(", "),
+        quote_identifier(table)
+    ))
+}
+
+/// Quote an identifier with backticks, e.g. `my table` becomes `` `my table` ``.
+pub fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+fn table_not_found(database: &str, table: &str) -> McpError {
+    let error_message = format!("Not found table `{table}` in database `{database}`");
+    error!(error_message);
+    McpError::new(ErrorCode::INVALID_PARAMS, error_message, None)
+}
+
+pub fn query_error(action: &str, e: mysql::Error) -> McpError {
+    let error_message = format!("Failed to {action}: {e}");
+    error!(error_message);
+    McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_identifier_escapes_backticks() {
+        assert_eq!(quote_identifier("docs"), "`docs`");
+        assert_eq!(quote_identifier("a`b"), "`a``b`");
+    }
+}
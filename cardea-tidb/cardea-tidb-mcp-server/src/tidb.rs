@@ -1,6 +1,11 @@
-use crate::TIDB_ACCESS_CONFIG;
+use crate::{
+    TIDB_ACCESS_CONFIG, TidbAccessConfig, documents, query,
+    schema::{self, quote_identifier},
+};
 use cardea_tidb_mcp_common::*;
-use mysql::prelude::*;
+use endpoints::embeddings::{EmbeddingRequest, EmbeddingsResponse, InputText};
+use mysql::{PooledConn, Row, prelude::*};
+use reqwest::header::AUTHORIZATION;
 use rmcp::{
     ErrorData as McpError, RoleServer, ServerHandler,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
@@ -9,7 +14,7 @@ use rmcp::{
     tool, tool_handler, tool_router,
 };
 use std::result::Result;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 const PROMPT_SEARCH_TOOL: &str = r#"
 You are a multilingual AI assistant. Your task is to (1) extract the most relevant and concise keywords or key phrases from the given user query, and (2) return a tool call that invokes the `search` tool with the extracted keywords.
@@ -47,16 +52,37 @@ Examples:
     </tool_call>
 "#;
 
+/// Default rank constant of the reciprocal rank fusion in the hybrid search
+const DEFAULT_RANK_CONSTANT: u64 = 60;
+
+/// Default number of rows returned by the sample rows tool
+const DEFAULT_SAMPLE_ROWS: u64 = 5;
+
+/// Number of candidates taken from each ranking of the hybrid search, per hit returned
+const HYBRID_CANDIDATES_PER_HIT: u64 = 4;
+
+/// Tools that modify the database, only registered with `--enable-write-tools`
+const WRITE_TOOLS: [&str; 3] = [
+    "insert_documents",
+    "delete_documents",
+    "ensure_fulltext_index",
+];
+
 #[derive(Debug, Clone)]
 pub struct TidbServer {
     tool_router: ToolRouter<Self>,
 }
 #[tool_router]
 impl TidbServer {
-    pub fn new() -> Self {
-        Self {
-            tool_router: Self::tool_router(),
+    pub fn new(enable_write_tools: bool) -> Self {
+        let mut tool_router = Self::tool_router();
+        if !enable_write_tools {
+            for name in WRITE_TOOLS {
+                tool_router.remove_route(name);
+            }
         }
+
+        Self { tool_router }
     }
 
     #[tool(description = "Perform keyword search in TiDB")]
@@ -77,73 +103,7 @@ impl TidbServer {
             }
         };
 
-        // get connection
-        info!("Getting connection...");
-        let mut conn = config.pool.get_conn().map_err(|e| {
-            let error_message = format!("Failed to get connection: {e}");
-
-            error!(error_message);
-
-            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-        })?;
-
-        // test connection
-        info!("Testing connection...");
-        let version: String = match conn.query_first("SELECT VERSION()").map_err(|e| {
-            let error_message = format!("Failed to query version: {e}");
-
-            error!(error_message);
-
-            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-        })? {
-            Some(version) => version,
-            None => {
-                let error_message = "Failed to query version";
-
-                error!(error_message);
-
-                return Err(McpError::new(
-                    ErrorCode::INTERNAL_ERROR,
-                    error_message,
-                    None,
-                ));
-            }
-        };
-        info!("Connected to TiDB Cloud! Version: {}", version);
-
-        // check if table exists
-        info!("Checking if table exists...");
-        let check_table_sql = format!(
-            "SELECT COUNT(*) FROM information_schema.tables
-                WHERE table_schema = '{}' AND table_name = '{}'",
-            config.database, config.table_name
-        );
-        info!("Executing check table SQL: {}", check_table_sql);
-        let table_exists: i32 = conn
-            .query_first(&check_table_sql)
-            .map_err(|e| {
-                let error_message = format!("Failed to check table: {e}");
-
-                error!(error_message);
-
-                McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
-            })?
-            .unwrap_or(0);
-
-        if table_exists == 0 {
-            let error_message = format!(
-                "Not found table `{}` in database `{}`",
-                config.table_name, config.database
-            );
-
-            error!(error_message);
-
-            return Err(McpError::new(
-                ErrorCode::INTERNAL_ERROR,
-                error_message,
-                None,
-            ));
-        }
+        let mut conn = get_conn(&config)?;
 
         // execute full-text search
         info!("\nExecuting full-text search for '{}'...", query);
@@ -169,14 +129,17 @@ impl TidbServer {
             //     })?;
         }
 
-        let safe_query = query.replace("'", "''"); // ! This is a workaround for the issue that the query contains single quotes.
+        let safe_query = escape_string(&query);
 
         let search_sql = format!(
             r"SELECT * FROM {}
                     WHERE fts_match_word('{}', content)
                     ORDER BY fts_match_word('{}', content)
                     DESC LIMIT {}",
-            config.table_name, safe_query, safe_query, config.limit
+            quote_identifier(&config.table_name),
+            safe_query,
+            safe_query,
+            config.limit
         );
 
         let hits: Vec<TidbSearchHit> = conn.query(&search_sql).map_err(|e| {
@@ -201,6 +164,457 @@ impl TidbServer {
 
         Ok(CallToolResult::success(vec![content]))
     }
+
+    #[tool(
+        description = "Perform vector search in TiDB, ranking the rows by the cosine distance between their vector column and the query vector. Exactly one of `query` (embedded by the embedding service) and `vector` must be provided."
+    )]
+    async fn vector_search(
+        &self,
+        Parameters(TidbVectorSearchRequest {
+            query,
+            vector,
+            limit,
+        }): Parameters<TidbVectorSearchRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let limit = search_limit(limit, config.limit, config.max_limit)?;
+        let vector = match (query, vector) {
+            (None, Some(vector)) => vector,
+            (Some(query), None) => compute_embedding(&config, &query).await?,
+            (Some(_), Some(_)) => {
+                let error_message = "Only one of `query` and `vector` may be provided";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+            (None, None) => {
+                let error_message = "Either `query` or `vector` must be provided";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+        let vector = vector_literal(&vector)?;
+
+        let mut conn = get_conn(&config)?;
+
+        info!(
+            "Executing vector search on column `{}`...",
+            config.vector_column
+        );
+        let search_sql =
+            vector_search_sql(&config.table_name, &config.vector_column, &vector, limit);
+
+        let rows: Vec<Row> = conn.query(&search_sql).map_err(|e| {
+            let error_message = format!("Failed to execute vector search: {e}");
+
+            error!(error_message);
+
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+        let hits: Vec<TidbVectorSearchHit> = read_hits(rows, "vector search")?;
+
+        info!("Found {} matching records", hits.len());
+
+        let content = Content::json(TidbVectorSearchResponse { hits })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Perform hybrid search in TiDB, combining the full-text search and the vector search rankings with reciprocal rank fusion."
+    )]
+    async fn hybrid_search(
+        &self,
+        Parameters(TidbHybridSearchRequest {
+            query,
+            limit,
+            rank_constant,
+        }): Parameters<TidbHybridSearchRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let limit = search_limit(limit, config.limit, config.max_limit)?;
+        let vector = vector_literal(&compute_embedding(&config, &query).await?)?;
+        let rank_constant = rank_constant.unwrap_or(DEFAULT_RANK_CONSTANT);
+
+        let mut conn = get_conn(&config)?;
+
+        info!("Executing hybrid search for '{}'...", query);
+        let search_sql = hybrid_search_sql(
+            &config.table_name,
+            &config.vector_column,
+            &escape_string(&query),
+            &vector,
+            limit,
+            rank_constant,
+        );
+
+        let rows: Vec<Row> = conn.query(&search_sql).map_err(|e| {
+            let error_message = format!("Failed to execute hybrid search: {e}");
+
+            error!(error_message);
+
+            McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+        })?;
+        let hits: Vec<TidbHybridSearchHit> = read_hits(rows, "hybrid search")?;
+
+        info!("Found {} matching records", hits.len());
+
+        let content = Content::json(TidbHybridSearchResponse { hits })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Run a read-only SQL query (SELECT) in TiDB and return the columns and rows of the result. Statements that modify data or schema are rejected."
+    )]
+    async fn query(
+        &self,
+        Parameters(TidbQueryRequest { sql, max_rows }): Parameters<TidbQueryRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let max_rows = match max_rows {
+            Some(0) => {
+                let error_message = "`max_rows` must be greater than 0";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+            Some(max_rows) => max_rows.min(config.query_max_rows),
+            None => config.query_max_rows,
+        };
+
+        let sql = query::prepare_query(&sql, max_rows)?;
+
+        let mut conn = get_conn(&config)?;
+
+        info!("Executing query: {}", sql);
+        let response = run_query(&mut conn, &config, &sql, max_rows)?;
+
+        info!(
+            "Query returned {} rows (truncated: {})",
+            response.rows.len(),
+            response.truncated
+        );
+
+        let content = Content::json(response)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(description = "List the tables and views of the TiDB database")]
+    async fn list_tables(&self) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let mut conn = get_conn(&config)?;
+
+        let tables = schema::list_tables(&mut conn, &config.database)?;
+
+        let content = Content::json(TidbListTablesResponse { tables })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Describe a table of the TiDB database: its columns with their types, and its indexes, including full-text indexes"
+    )]
+    async fn describe_table(
+        &self,
+        Parameters(TidbDescribeTableRequest { table }): Parameters<TidbDescribeTableRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let mut conn = get_conn(&config)?;
+
+        let description = schema::describe_table(&mut conn, &config.database, &table)?;
+
+        let content = Content::json(description)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Return the first rows of a table of the TiDB database, to see what its data looks like. VECTOR columns are left out."
+    )]
+    async fn sample_rows(
+        &self,
+        Parameters(TidbSampleRowsRequest { table, limit }): Parameters<TidbSampleRowsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        let limit = match limit {
+            Some(0) => {
+                let error_message = "`limit` must be greater than 0";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    error_message,
+                    None,
+                ));
+            }
+            Some(limit) => limit.min(config.query_max_rows),
+            None => DEFAULT_SAMPLE_ROWS.min(config.query_max_rows),
+        };
+
+        let mut conn = get_conn(&config)?;
+
+        let sql = schema::sample_rows_sql(&mut conn, &config.database, &table, limit)?;
+
+        info!("Sampling rows: {}", sql);
+        let response = run_query(&mut conn, &config, &sql, limit)?;
+
+        let content = Content::json(response)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Insert documents into the TiDB table configured on the server, replacing the rows with the same ids. If the table has a vector column, documents without a vector are embedded by the embedding service."
+    )]
+    async fn insert_documents(
+        &self,
+        Parameters(TidbInsertDocumentsRequest { documents }): Parameters<
+            TidbInsertDocumentsRequest,
+        >,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        if documents.is_empty() {
+            let error_message = "`documents` must not be empty";
+            error!(error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+        let table = &config.table_name;
+
+        let mut conn = get_write_conn(&config)?;
+
+        let has_vector_column =
+            documents::has_column(&mut conn, &config.database, table, &config.vector_column)?;
+
+        let mut rows = Vec::with_capacity(documents.len());
+        for TidbDocument {
+            id,
+            title,
+            content,
+            vector,
+        } in documents
+        {
+            let vector = match (vector, has_vector_column) {
+                (Some(vector), true) => Some(vector_literal(&vector)?),
+                (Some(_), false) => {
+                    let error_message = format!(
+                        "Table `{}` has no vector column `{}`",
+                        table, config.vector_column
+                    );
+                    error!(error_message);
+                    return Err(McpError::new(
+                        ErrorCode::INVALID_PARAMS,
+                        error_message,
+                        None,
+                    ));
+                }
+                (None, true) if config.embedding_service.is_some() => Some(vector_literal(
+                    &compute_embedding(&config, &content).await?,
+                )?),
+                (None, _) => None,
+            };
+            rows.push((id, title, content, vector));
+        }
+
+        let vector_column = has_vector_column.then_some(config.vector_column.as_str());
+        let ids = documents::insert_documents(&mut conn, table, vector_column, rows)?;
+
+        info!("Inserted {} documents into `{}`", ids.len(), table);
+
+        let content = Content::json(TidbInsertDocumentsResponse { ids })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Delete documents from the TiDB table configured on the server by their ids"
+    )]
+    async fn delete_documents(
+        &self,
+        Parameters(TidbDeleteDocumentsRequest { ids }): Parameters<TidbDeleteDocumentsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        if ids.is_empty() {
+            let error_message = "`ids` must not be empty";
+            error!(error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+        let table = &config.table_name;
+
+        let mut conn = get_write_conn(&config)?;
+
+        let deleted = documents::delete_documents(&mut conn, table, &ids)?;
+
+        info!("Deleted {} documents from `{}`", deleted, table);
+
+        let content = Content::json(TidbDeleteDocumentsResponse { deleted })?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "Make sure the TiDB table configured on the server exists with a full-text index on its `content` column, creating the table (with `id`, `title` and `content` columns, and optionally a vector column) or the index with the chosen parser if absent"
+    )]
+    async fn ensure_fulltext_index(
+        &self,
+        Parameters(TidbEnsureFulltextIndexRequest {
+            parser,
+            vector_dimensions,
+        }): Parameters<TidbEnsureFulltextIndexRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = match TIDB_ACCESS_CONFIG.get() {
+            Some(config) => config.read().await,
+            None => {
+                let error_message = "TIDB_ACCESS_CONFIG is not set";
+                error!(error_message);
+                return Err(McpError::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    error_message,
+                    None,
+                ));
+            }
+        };
+
+        if vector_dimensions == Some(0) {
+            let error_message = "`vector_dimensions` must be greater than 0";
+            error!(error_message);
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ));
+        }
+        let table = &config.table_name;
+
+        let mut conn = get_write_conn(&config)?;
+
+        let response = documents::ensure_fulltext_index(
+            &mut conn,
+            &config.database,
+            table,
+            &config.vector_column,
+            parser.unwrap_or_default(),
+            vector_dimensions,
+        )?;
+
+        let content = Content::json(response)?;
+
+        Ok(CallToolResult::success(vec![content]))
+    }
 }
 
 #[tool_handler]
@@ -269,3 +683,393 @@ impl ServerHandler for TidbServer {
         }
     }
 }
+
+/// Get a connection from the pool. The version and the table are only checked on the first
+/// connection after the database was unreachable, as found by the health check.
+fn get_conn(config: &TidbAccessConfig) -> Result<PooledConn, McpError> {
+    cardea_tidb_mcp_common::get_conn(
+        &config.pool,
+        &config.database,
+        &config.table_name,
+        &config.schema_checked,
+    )
+}
+
+/// Get a connection from the pool for the write tools. The configured table is not checked, as
+/// they may create it.
+fn get_write_conn(config: &TidbAccessConfig) -> Result<PooledConn, McpError> {
+    cardea_tidb_mcp_common::connect(&config.pool, &config.schema_checked)
+}
+
+/// Run a query with the configured execution time limit, and read at most `max_rows` rows.
+fn run_query(
+    conn: &mut PooledConn,
+    config: &TidbAccessConfig,
+    sql: &str,
+    max_rows: u64,
+) -> Result<TidbQueryResponse, McpError> {
+    // the execution time limit is a session variable, so reset it before the connection
+    // returns to the pool
+    conn.query_drop(format!(
+        "SET SESSION max_execution_time = {}",
+        config.query_timeout * 1000
+    ))
+    .map_err(|e| {
+        let error_message = format!("Failed to set the query timeout: {e}");
+
+        error!(error_message);
+
+        McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+    })?;
+
+    let response = read_rows(conn, sql, max_rows);
+
+    if let Err(e) = conn.query_drop("SET SESSION max_execution_time = 0") {
+        warn!("Failed to reset the query timeout: {e}");
+    }
+
+    response.map_err(|e| {
+        let error_message = format!("Failed to execute query: {e}");
+
+        error!(error_message);
+
+        McpError::new(ErrorCode::INVALID_PARAMS, error_message, None)
+    })
+}
+
+/// Run a query with the binary protocol, which returns typed values, and read at most `max_rows`
+/// rows. TiDB rejects `SET TRANSACTION READ ONLY` by default, so the query is not wrapped in a
+/// read-only transaction: `prepare_query` only lets reads through.
+fn read_rows(
+    conn: &mut PooledConn,
+    sql: &str,
+    max_rows: u64,
+) -> Result<TidbQueryResponse, mysql::Error> {
+    let mut result = conn.exec_iter(sql, ())?;
+
+    let column_types: Vec<_> = result
+        .columns()
+        .as_ref()
+        .iter()
+        .map(|column| column.column_type())
+        .collect();
+    let columns = result
+        .columns()
+        .as_ref()
+        .iter()
+        .map(|column| query::column(column.name_str().into_owned(), column.column_type()))
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    for row in result.by_ref() {
+        if rows.len() as u64 == max_rows {
+            truncated = true;
+            break;
+        }
+        let values = row?
+            .unwrap()
+            .into_iter()
+            .zip(&column_types)
+            .map(|(value, column_type)| query::to_json(value, *column_type))
+            .collect();
+        rows.push(values);
+    }
+    // the query is limited to `max_rows + 1` rows, so at most one row is left to drain
+    drop(result);
+
+    Ok(TidbQueryResponse {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+/// Build the vector search query. Rows without a vector are skipped, as their distance is NULL,
+/// which sorts first.
+fn vector_search_sql(table: &str, vector_column: &str, vector: &str, limit: u64) -> String {
+    let table = quote_identifier(table);
+    let vector_column = quote_identifier(vector_column);
+
+    format!(
+        r"SELECT id, title, content, VEC_COSINE_DISTANCE({vector_column}, '{vector}') AS distance
+            FROM {table}
+            WHERE {vector_column} IS NOT NULL
+            ORDER BY distance
+            LIMIT {limit}"
+    )
+}
+
+/// Build the hybrid search query. Each of the full-text and vector searches ranks its best
+/// candidates, and the rows are ordered by the sum of `1 / (rank_constant + rank)` over the
+/// rankings they appear in.
+fn hybrid_search_sql(
+    table: &str,
+    vector_column: &str,
+    query: &str,
+    vector: &str,
+    limit: u64,
+    rank_constant: u64,
+) -> String {
+    let table = quote_identifier(table);
+    let vector_column = quote_identifier(vector_column);
+    let candidates = limit.saturating_mul(HYBRID_CANDIDATES_PER_HIT);
+
+    format!(
+        r"SELECT t.id, t.title, t.content, fused.score
+            FROM (
+                SELECT id, SUM(1e0 / ({rank_constant} + ranking)) AS score
+                FROM (
+                    SELECT id, ROW_NUMBER() OVER (ORDER BY relevance DESC) AS ranking
+                    FROM (
+                        SELECT id, fts_match_word('{query}', content) AS relevance
+                        FROM {table}
+                        WHERE fts_match_word('{query}', content)
+                        ORDER BY fts_match_word('{query}', content) DESC
+                        LIMIT {candidates}
+                    ) fts
+                    UNION ALL
+                    SELECT id, ROW_NUMBER() OVER (ORDER BY distance) AS ranking
+                    FROM (
+                        SELECT id, VEC_COSINE_DISTANCE({vector_column}, '{vector}') AS distance
+                        FROM {table}
+                        WHERE {vector_column} IS NOT NULL
+                        ORDER BY distance
+                        LIMIT {candidates}
+                    ) vec
+                ) rankings
+                GROUP BY id
+            ) fused
+            JOIN {table} t ON t.id = fused.id
+            ORDER BY fused.score DESC
+            LIMIT {limit}"
+    )
+}
+
+/// Convert the rows of a search into hits. A row that does not match the hit, e.g. with an
+/// unexpected NULL, is an error instead of a panic.
+fn read_hits<T: FromRow>(rows: Vec<Row>, search: &str) -> Result<Vec<T>, McpError> {
+    rows.into_iter()
+        .map(|row| {
+            T::from_row_opt(row).map_err(|e| {
+                let error_message = format!("Failed to read the {search} results: {e}");
+
+                error!(error_message);
+
+                McpError::new(ErrorCode::INTERNAL_ERROR, error_message, None)
+            })
+        })
+        .collect()
+}
+
+/// Resolve the number of hits of the vector and hybrid search: `default` if not set, and at most
+/// `max`.
+fn search_limit(limit: Option<u64>, default: u64, max: u64) -> Result<u64, McpError> {
+    match limit {
+        Some(0) => {
+            let error_message = "`limit` must be greater than 0";
+            error!(error_message);
+            Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                error_message,
+                None,
+            ))
+        }
+        Some(limit) => Ok(limit.min(max)),
+        None => Ok(default),
+    }
+}
+
+/// Format a vector as a TiDB vector literal, e.g. `[0.1,0.2]`.
+fn vector_literal(vector: &[f64]) -> Result<String, McpError> {
+    if vector.is_empty() || vector.iter().any(|value| !value.is_finite()) {
+        let error_message = "The vector must be non-empty and only contain finite numbers";
+        error!(error_message);
+        return Err(McpError::new(
+            ErrorCode::INVALID_PARAMS,
+            error_message,
+            None,
+        ));
+    }
+
+    let values: Vec<String> = vector.iter().map(|value| value.to_string()).collect();
+    Ok(format!("[{}]", values.join(",")))
+}
+
+/// Escape a string to be embedded in a single-quoted SQL string literal.
+fn escape_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "''")
+}
+
+/// Compute the embedding of the query with the configured embedding service.
+async fn compute_embedding(
+    config: &TidbAccessConfig,
+    query: impl AsRef<str>,
+) -> Result<Vec<f64>, McpError> {
+    match &config.embedding_service {
+        Some(service) => {
+            let embedding_service_url = format!("{}/embeddings", service.url.trim_end_matches('/'));
+
+            // create a embedding request
+            let embedding_request = EmbeddingRequest {
+                model: None,
+                input: InputText::String(query.as_ref().to_string()),
+                encoding_format: None,
+                user: None,
+            };
+
+            let mut request = reqwest::Client::new()
+                .post(&embedding_service_url)
+                .json(&embedding_request);
+            if let Some(api_key) = &service.api_key {
+                request = request.header(AUTHORIZATION, api_key);
+            }
+
+            let response = request.send().await.map_err(|e| {
+                let err_msg = format!("Failed to send the embedding request: {e}");
+                error!("{}", err_msg);
+                McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None)
+            })?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let err_msg = format!("Failed to compute the embedding. Status: {status}");
+                error!("{}", err_msg);
+                return Err(McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None));
+            }
+
+            // parse the response
+            let embedding_response = response.json::<EmbeddingsResponse>().await.map_err(|e| {
+                let err_msg = format!("Failed to parse embeddings response: {e}");
+                error!("{}", err_msg);
+                McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None)
+            })?;
+
+            let embedding = embedding_response.data.first().ok_or_else(|| {
+                let err_msg = "No embeddings returned";
+                error!("{}", err_msg);
+                McpError::new(ErrorCode::INTERNAL_ERROR, err_msg, None)
+            })?;
+
+            Ok(embedding.embedding.to_vec())
+        }
+        None => {
+            let error_message = "Embedding service is not configured. Start the server with `--embedding-service` to enable searching by query.";
+            error!("{}", error_message);
+            Err(McpError::new(
+                ErrorCode::INVALID_REQUEST,
+                error_message,
+                None,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::{Column, Value, consts::ColumnType};
+    use mysql_common::row::new_row;
+    use std::sync::Arc;
+
+    #[test]
+    fn vector_literal_rejects_invalid_vectors() {
+        assert_eq!(vector_literal(&[0.5, -1.0, 2.25]).unwrap(), "[0.5,-1,2.25]");
+        assert!(vector_literal(&[]).is_err());
+        assert!(vector_literal(&[f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn escape_string_escapes_quotes_and_backslashes() {
+        assert_eq!(escape_string(r"it's"), "it''s");
+        assert_eq!(escape_string(r"\' OR 1=1 --"), r"\\'' OR 1=1 --");
+    }
+
+    #[test]
+    fn search_limit_is_bounded() {
+        assert_eq!(search_limit(None, 10, 100).unwrap(), 10);
+        assert_eq!(search_limit(Some(20), 10, 100).unwrap(), 20);
+        assert_eq!(search_limit(Some(u64::MAX), 10, 100).unwrap(), 100);
+        assert_eq!(
+            search_limit(Some(0), 10, 100).unwrap_err().code,
+            ErrorCode::INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn vector_search_skips_rows_without_vector() {
+        let sql = vector_search_sql("docs", "embed`ding", "[0.5,1]", 5);
+
+        assert!(sql.contains("VEC_COSINE_DISTANCE(`embed``ding`, '[0.5,1]')"));
+        assert!(sql.contains("FROM `docs`"));
+        assert!(sql.contains("WHERE `embed``ding` IS NOT NULL"));
+        assert!(sql.ends_with("LIMIT 5"));
+    }
+
+    #[test]
+    fn null_distance_is_an_error() {
+        let columns: Arc<[Column]> = ["id", "title", "content", "distance"]
+            .into_iter()
+            .map(|name| Column::new(ColumnType::MYSQL_TYPE_NULL).with_name(name.as_bytes()))
+            .collect();
+        let row = |distance: Value| {
+            new_row(
+                vec![
+                    Value::Int(1),
+                    Value::from("title"),
+                    Value::from("content"),
+                    distance,
+                ],
+                columns.clone(),
+            )
+        };
+
+        let hits: Vec<TidbVectorSearchHit> =
+            read_hits(vec![row(Value::Double(0.25))], "vector search").unwrap();
+        assert_eq!(hits[0].distance, 0.25);
+
+        let error =
+            read_hits::<TidbVectorSearchHit>(vec![row(Value::NULL)], "vector search").unwrap_err();
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    }
+
+    #[test]
+    fn build_hybrid_search_sql() {
+        let sql = hybrid_search_sql("docs", "embedding", "tidb", "[0.5,1]", 5, 60);
+
+        assert!(sql.contains("SUM(1e0 / (60 + ranking))"));
+        assert!(sql.contains("WHERE fts_match_word('tidb', content)"));
+        assert!(sql.contains("VEC_COSINE_DISTANCE(`embedding`, '[0.5,1]')"));
+        assert!(sql.contains("WHERE `embedding` IS NOT NULL"));
+        assert!(sql.contains("FROM `docs`"));
+        assert_eq!(sql.matches("LIMIT 20").count(), 2);
+        assert!(sql.ends_with("LIMIT 5"));
+
+        // the number of candidates saturates instead of overflowing
+        let sql = hybrid_search_sql("docs", "embedding", "tidb", "[0.5,1]", u64::MAX, 60);
+        assert_eq!(sql.matches(&format!("LIMIT {}", u64::MAX)).count(), 3);
+    }
+
+    #[test]
+    fn write_tools_are_opt_in() {
+        let names = |server: TidbServer| -> Vec<String> {
+            server
+                .tool_router
+                .list_all()
+                .into_iter()
+                .map(|tool| tool.name.to_string())
+                .collect()
+        };
+
+        let tools = names(TidbServer::new(false));
+        assert!(tools.contains(&"search".to_string()));
+        for name in WRITE_TOOLS {
+            assert!(!tools.contains(&name.to_string()), "{name}");
+        }
+
+        let tools = names(TidbServer::new(true));
+        for name in WRITE_TOOLS {
+            assert!(tools.contains(&name.to_string()), "{name}");
+        }
+    }
+}